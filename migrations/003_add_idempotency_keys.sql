@@ -0,0 +1,13 @@
+-- idempotency keys for POST /api/transactions.
+-- one row per (business, key); the row is inserted in the same db transaction
+-- as the money movement, so a rolled back request leaves no key behind.
+
+CREATE TABLE idempotency_keys (
+    business_id    UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    key            TEXT NOT NULL,
+    request_hash   TEXT NOT NULL,
+    response_body  JSONB,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (business_id, key)
+);
@@ -0,0 +1,9 @@
+-- webhook deliveries are now signed with the per-endpoint secret.
+-- endpoints created before this change were stored with an empty secret;
+-- give them a random one so every delivery carries a signature.
+
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+UPDATE webhook_endpoints
+SET secret = 'whsec_' || encode(gen_random_bytes(32), 'hex')
+WHERE secret = '';
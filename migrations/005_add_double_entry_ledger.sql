@@ -0,0 +1,102 @@
+-- double-entry ledger.
+-- every transaction gets one journal entry whose postings sum to zero per currency.
+-- accounts.balance stays as a cached projection of postings for customer accounts.
+-- system accounts represent the "world" side of credits and debits (one per business + currency);
+-- their balance is not cached (it would be a hot row), it is derived from postings on read.
+
+ALTER TABLE accounts
+ADD COLUMN is_system BOOLEAN NOT NULL DEFAULT false;
+
+DROP INDEX idx_accounts_business_id_name;
+
+CREATE UNIQUE INDEX idx_accounts_business_id_name
+ON accounts (business_id, name)
+WHERE NOT is_system;
+
+CREATE UNIQUE INDEX idx_accounts_system_currency
+ON accounts (business_id, currency)
+WHERE is_system;
+
+CREATE TABLE journal_entries (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id     UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    transaction_id  UUID NOT NULL UNIQUE REFERENCES transactions(id),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_journal_entries_business_id
+    ON journal_entries(business_id);
+
+CREATE TABLE postings (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    journal_entry_id  UUID NOT NULL REFERENCES journal_entries(id) ON DELETE CASCADE,
+    account_id        UUID NOT NULL REFERENCES accounts(id),
+    currency          CHAR(3) NOT NULL,
+    amount            BIGINT NOT NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT posting_amount_non_zero CHECK (amount <> 0)
+);
+
+CREATE INDEX idx_postings_journal_entry_id
+    ON postings(journal_entry_id);
+
+CREATE INDEX idx_postings_account_id
+    ON postings(account_id);
+
+-- checked at commit so all postings of an entry can be inserted first.
+CREATE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM postings
+        WHERE journal_entry_id = NEW.journal_entry_id
+        GROUP BY currency
+        HAVING SUM(amount) <> 0
+    ) THEN
+        RAISE EXCEPTION 'journal entry % is not balanced', NEW.journal_entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER postings_balanced
+    AFTER INSERT ON postings
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+-- backfill: system accounts, then one journal entry + postings per existing transaction.
+
+INSERT INTO accounts (business_id, name, currency, balance, is_system)
+SELECT DISTINCT business_id, 'system:external:' || currency, currency, 0, true
+FROM accounts
+WHERE NOT is_system;
+
+INSERT INTO journal_entries (business_id, transaction_id, created_at)
+SELECT business_id, id, created_at
+FROM transactions;
+
+INSERT INTO postings (journal_entry_id, account_id, currency, amount, created_at)
+SELECT je.id, t.dest_account_id, d.currency, t.amount, t.created_at
+FROM transactions t
+JOIN journal_entries je ON je.transaction_id = t.id
+JOIN accounts d ON d.id = t.dest_account_id
+UNION ALL
+SELECT je.id, t.source_account_id, s.currency, -t.amount, t.created_at
+FROM transactions t
+JOIN journal_entries je ON je.transaction_id = t.id
+JOIN accounts s ON s.id = t.source_account_id
+UNION ALL
+SELECT je.id, w.id, w.currency, -t.amount, t.created_at
+FROM transactions t
+JOIN journal_entries je ON je.transaction_id = t.id
+JOIN accounts d ON d.id = t.dest_account_id
+JOIN accounts w ON w.business_id = t.business_id AND w.is_system AND w.currency = d.currency
+WHERE t.type = 'credit'
+UNION ALL
+SELECT je.id, w.id, w.currency, t.amount, t.created_at
+FROM transactions t
+JOIN journal_entries je ON je.transaction_id = t.id
+JOIN accounts s ON s.id = t.source_account_id
+JOIN accounts w ON w.business_id = t.business_id AND w.is_system AND w.currency = s.currency
+WHERE t.type = 'debit';
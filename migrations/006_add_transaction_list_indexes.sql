@@ -0,0 +1,14 @@
+-- keyset pagination for GET /api/transactions orders by (created_at DESC, id DESC).
+-- the account indexes serve the account_id filter, which matches either side.
+
+CREATE INDEX idx_transactions_business_created_id
+    ON transactions (business_id, created_at DESC, id DESC);
+
+CREATE INDEX idx_transactions_business_type_created_id
+    ON transactions (business_id, type, created_at DESC, id DESC);
+
+CREATE INDEX idx_transactions_source_created_id
+    ON transactions (source_account_id, created_at DESC, id DESC);
+
+CREATE INDEX idx_transactions_dest_created_id
+    ON transactions (dest_account_id, created_at DESC, id DESC);
@@ -0,0 +1,41 @@
+-- account statements: postings get a global sequence (statement order) and the
+-- account balance right after the posting was applied.
+-- balance_after is NULL for system accounts, whose balance is not cached.
+
+ALTER TABLE postings
+ADD COLUMN seq BIGINT,
+ADD COLUMN balance_after BIGINT;
+
+-- backfilled postings were inserted in arbitrary order; number them chronologically.
+WITH ordered AS (
+    SELECT p.id, row_number() OVER (ORDER BY t.created_at, t.id, p.amount) AS rn
+    FROM postings p
+    JOIN journal_entries je ON je.id = p.journal_entry_id
+    JOIN transactions t ON t.id = je.transaction_id
+)
+UPDATE postings p
+SET seq = ordered.rn
+FROM ordered
+WHERE ordered.id = p.id;
+
+CREATE SEQUENCE postings_seq_seq OWNED BY postings.seq;
+
+SELECT setval('postings_seq_seq', COALESCE((SELECT MAX(seq) FROM postings), 0) + 1, false);
+
+ALTER TABLE postings
+ALTER COLUMN seq SET DEFAULT nextval('postings_seq_seq'),
+ALTER COLUMN seq SET NOT NULL;
+
+WITH running AS (
+    SELECT p.id, SUM(p.amount) OVER (PARTITION BY p.account_id ORDER BY p.seq) AS balance_after
+    FROM postings p
+    JOIN accounts a ON a.id = p.account_id
+    WHERE NOT a.is_system
+)
+UPDATE postings p
+SET balance_after = running.balance_after
+FROM running
+WHERE running.id = p.id;
+
+CREATE UNIQUE INDEX idx_postings_account_seq
+    ON postings (account_id, seq DESC);
@@ -0,0 +1,5 @@
+-- api key management.
+-- revoked_at may now be in the future: a rotated key keeps working until then (overlap period).
+
+ALTER TABLE api_keys
+ADD COLUMN last_used_at TIMESTAMPTZ;
@@ -0,0 +1,6 @@
+-- HMAC_SECRET rotation: remember which secret each key_hash was derived with.
+-- value is a short SHA-256 fingerprint of the secret (never the secret itself).
+-- NULL = created before this migration and not used since (secret unknown).
+
+ALTER TABLE api_keys
+ADD COLUMN hmac_secret_fingerprint TEXT;
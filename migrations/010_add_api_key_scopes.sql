@@ -0,0 +1,13 @@
+-- scoped api keys.
+-- existing keys keep full access; the default also keeps the README bootstrap insert working.
+-- keys minted through the API always get an explicit scope list.
+
+ALTER TABLE api_keys
+ADD COLUMN scopes TEXT[] NOT NULL DEFAULT ARRAY[
+    'accounts:read',
+    'accounts:write',
+    'transactions:read',
+    'transactions:write',
+    'webhooks:manage',
+    'api_keys:manage'
+];
@@ -0,0 +1,5 @@
+-- account-restricted api keys.
+-- NULL = all accounts of the business; otherwise the key only sees these accounts.
+
+ALTER TABLE api_keys
+ADD COLUMN allowed_account_ids UUID[];
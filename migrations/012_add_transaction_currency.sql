@@ -0,0 +1,13 @@
+-- currency of the transaction amount, so responses can format it without joining accounts.
+-- backfilled from the source account (debit/transfer) or dest account (credit).
+
+ALTER TABLE transactions
+ADD COLUMN currency CHAR(3);
+
+UPDATE transactions t
+SET currency = a.currency
+FROM accounts a
+WHERE a.id = COALESCE(t.source_account_id, t.dest_account_id);
+
+ALTER TABLE transactions
+ALTER COLUMN currency SET NOT NULL;
@@ -0,0 +1,59 @@
+-- cross-currency transfers.
+-- fx_rates is a per-business rate table managed through /api/fx/rates: 1 base = rate quote.
+
+CREATE TABLE fx_rates (
+    business_id     UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    base_currency   CHAR(3) NOT NULL,
+    quote_currency  CHAR(3) NOT NULL,
+    rate            NUMERIC(24, 12) NOT NULL,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (business_id, base_currency, quote_currency),
+    CONSTRAINT fx_rate_positive CHECK (rate > 0),
+    CONSTRAINT fx_rate_distinct_currencies CHECK (base_currency <> quote_currency)
+);
+
+-- FX transfers debit `amount` in `currency` and credit `dest_amount` in `dest_currency`.
+-- all three columns are NULL for same-currency transactions.
+
+ALTER TABLE transactions
+ADD COLUMN dest_amount BIGINT,
+ADD COLUMN dest_currency CHAR(3),
+ADD COLUMN fx_rate NUMERIC(24, 12),
+ADD CONSTRAINT fx_columns_together CHECK (
+    (dest_amount IS NULL AND dest_currency IS NULL AND fx_rate IS NULL) OR
+    (dest_amount > 0 AND dest_currency IS NOT NULL AND fx_rate > 0 AND type = 'transfer')
+);
+
+-- FX postings go through per-currency system:fx:<CCY> accounts, so there can now be several
+-- system accounts per business + currency; they are told apart by name.
+
+DROP INDEX idx_accounts_system_currency;
+
+CREATE UNIQUE INDEX idx_accounts_system_name
+ON accounts (business_id, name)
+WHERE is_system;
+
+-- keys that had every scope keep every scope.
+
+UPDATE api_keys
+SET scopes = array_append(scopes, 'fx:manage')
+WHERE scopes @> ARRAY[
+    'accounts:read',
+    'accounts:write',
+    'transactions:read',
+    'transactions:write',
+    'webhooks:manage',
+    'api_keys:manage'
+];
+
+ALTER TABLE api_keys
+ALTER COLUMN scopes SET DEFAULT ARRAY[
+    'accounts:read',
+    'accounts:write',
+    'transactions:read',
+    'transactions:write',
+    'webhooks:manage',
+    'api_keys:manage',
+    'fx:manage'
+];
@@ -0,0 +1,25 @@
+-- FX quotes: a rate and both amounts locked for a short time between two accounts.
+-- a quote is consumed by exactly one transfer (used_at + transaction_id set in the same DB
+-- transaction as the transfer), so a failed transfer leaves it usable until it expires.
+
+CREATE TABLE fx_quotes (
+    id                 UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id        UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    source_account_id  UUID NOT NULL REFERENCES accounts(id),
+    dest_account_id    UUID NOT NULL REFERENCES accounts(id),
+    amount             BIGINT NOT NULL,
+    currency           CHAR(3) NOT NULL,
+    dest_amount        BIGINT NOT NULL,
+    dest_currency      CHAR(3) NOT NULL,
+    rate               NUMERIC(24, 12) NOT NULL,
+    expires_at         TIMESTAMPTZ NOT NULL,
+    used_at            TIMESTAMPTZ,
+    transaction_id     UUID REFERENCES transactions(id),
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT fx_quote_amounts_positive CHECK (amount > 0 AND dest_amount > 0 AND rate > 0),
+    CONSTRAINT fx_quote_used_with_transaction CHECK ((used_at IS NULL) = (transaction_id IS NULL))
+);
+
+CREATE INDEX idx_fx_quotes_business_id
+    ON fx_quotes(business_id);
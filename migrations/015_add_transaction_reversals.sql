@@ -0,0 +1,14 @@
+-- reversals are compensating transactions linked to the original by reversal_of.
+-- reversed_amount on the original is the running total reversed so far, in the original's
+-- `amount` units; the check keeps it from ever exceeding the original amount.
+
+ALTER TABLE transactions
+ADD COLUMN reversal_of UUID REFERENCES transactions(id),
+ADD COLUMN reversed_amount BIGINT NOT NULL DEFAULT 0,
+ADD CONSTRAINT reversed_amount_within_amount CHECK (
+    reversed_amount >= 0 AND reversed_amount <= amount
+);
+
+CREATE INDEX idx_transactions_reversal_of
+    ON transactions (reversal_of)
+    WHERE reversal_of IS NOT NULL;
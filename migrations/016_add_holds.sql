@@ -0,0 +1,47 @@
+-- holds (authorizations): reserve part of an account's balance without moving money.
+-- accounts.held_amount is a cached sum of the account's active holds, maintained under the
+-- account row lock like `balance`; available balance = balance - held_amount.
+
+ALTER TABLE accounts
+ADD COLUMN held_amount BIGINT NOT NULL DEFAULT 0,
+ADD CONSTRAINT held_amount_non_negative CHECK (held_amount >= 0);
+
+CREATE TYPE hold_status AS ENUM (
+    'active',
+    'captured',
+    'voided',
+    'expired'
+);
+
+-- a hold is captured at most once (fully or partially, the rest is released), voided, or
+-- expired by the hold worker; released_at is set whenever it stops being active.
+
+CREATE TABLE holds (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id      UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    account_id       UUID NOT NULL REFERENCES accounts(id),
+    amount           BIGINT NOT NULL,
+    currency         CHAR(3) NOT NULL,
+    status           hold_status NOT NULL DEFAULT 'active',
+    captured_amount  BIGINT,
+    transaction_id   UUID REFERENCES transactions(id),
+    expires_at       TIMESTAMPTZ NOT NULL,
+    released_at      TIMESTAMPTZ,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT hold_amount_positive CHECK (amount > 0),
+    CONSTRAINT hold_released_unless_active CHECK ((status = 'active') = (released_at IS NULL)),
+    CONSTRAINT hold_capture_columns CHECK (
+        (status = 'captured') = (transaction_id IS NOT NULL) AND
+        (status = 'captured') = (captured_amount IS NOT NULL) AND
+        (captured_amount IS NULL OR (captured_amount > 0 AND captured_amount <= amount))
+    )
+);
+
+CREATE INDEX idx_holds_account_created
+    ON holds (account_id, created_at DESC);
+
+-- the hold worker's scan for expired holds.
+CREATE INDEX idx_holds_active_expires_at
+    ON holds (expires_at)
+    WHERE status = 'active';
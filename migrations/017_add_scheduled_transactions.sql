@@ -0,0 +1,74 @@
+-- scheduled transactions: a transaction template executed by the scheduler at next_run_at,
+-- once or on a daily/weekly/monthly recurrence until end_at. Occurrence n is at
+-- start_at + n * frequency (months are clamped to the month's last day), so runs don't drift.
+
+CREATE TYPE schedule_frequency AS ENUM (
+    'daily',
+    'weekly',
+    'monthly'
+);
+
+CREATE TYPE schedule_status AS ENUM (
+    'active',
+    'completed',
+    'cancelled'
+);
+
+CREATE TABLE scheduled_transactions (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id       UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    type              transaction_type NOT NULL,
+    source_account_id UUID REFERENCES accounts(id),
+    dest_account_id   UUID REFERENCES accounts(id),
+    amount            BIGINT NOT NULL,
+    frequency         schedule_frequency,
+    start_at          TIMESTAMPTZ NOT NULL,
+    end_at            TIMESTAMPTZ,
+    status            schedule_status NOT NULL DEFAULT 'active',
+    run_count         INT NOT NULL DEFAULT 0,
+    next_run_at       TIMESTAMPTZ,
+    last_run_at       TIMESTAMPTZ,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT scheduled_amount_positive CHECK (amount > 0),
+    CONSTRAINT scheduled_end_needs_frequency CHECK (end_at IS NULL OR frequency IS NOT NULL),
+    CONSTRAINT scheduled_end_after_start CHECK (end_at IS NULL OR end_at >= start_at),
+    CONSTRAINT scheduled_next_run_iff_active CHECK ((status = 'active') = (next_run_at IS NOT NULL))
+);
+
+CREATE INDEX idx_scheduled_transactions_business_created
+    ON scheduled_transactions (business_id, created_at DESC);
+
+-- the scheduler's scan for due schedules.
+CREATE INDEX idx_scheduled_transactions_active_next_run
+    ON scheduled_transactions (next_run_at)
+    WHERE status = 'active';
+
+CREATE TYPE scheduled_run_status AS ENUM (
+    'succeeded',
+    'failed'
+);
+
+-- one row per executed occurrence; error is the API error body the request would have got.
+
+CREATE TABLE scheduled_transaction_runs (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    schedule_id     UUID NOT NULL REFERENCES scheduled_transactions(id) ON DELETE CASCADE,
+    occurrence      INT NOT NULL,
+    scheduled_for   TIMESTAMPTZ NOT NULL,
+    status          scheduled_run_status NOT NULL,
+    transaction_id  UUID REFERENCES transactions(id),
+    error           JSONB,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT scheduled_run_outcome CHECK (
+        (status = 'succeeded' AND transaction_id IS NOT NULL AND error IS NULL) OR
+        (status = 'failed' AND transaction_id IS NULL AND error IS NOT NULL)
+    ),
+    CONSTRAINT scheduled_run_occurrence_unique UNIQUE (schedule_id, occurrence)
+);
+
+-- webhook events are no longer always about a transaction (e.g. a failed scheduled run).
+ALTER TABLE webhook_events
+ALTER COLUMN transaction_id DROP NOT NULL;
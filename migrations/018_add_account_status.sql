@@ -0,0 +1,16 @@
+-- account lifecycle: frozen accounts can receive money but not send it; closed accounts are
+-- final and take part in no movement. Only an empty account (no balance, no active holds)
+-- can be closed.
+
+CREATE TYPE account_status AS ENUM (
+    'active',
+    'frozen',
+    'closed'
+);
+
+ALTER TABLE accounts
+ADD COLUMN status account_status NOT NULL DEFAULT 'active',
+ADD COLUMN status_changed_at TIMESTAMPTZ,
+ADD CONSTRAINT closed_account_empty CHECK (
+    status <> 'closed' OR (balance = 0 AND held_amount = 0)
+);
@@ -0,0 +1,27 @@
+-- free-form metadata (a JSON object, limits enforced by the API) and an optional caller-side
+-- reference (e.g. an order id) on accounts and transactions. Both are filterable with exact
+-- matches: metadata with containment (@>), external_reference with equality.
+
+ALTER TABLE accounts
+ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}',
+ADD COLUMN external_reference TEXT,
+ADD CONSTRAINT account_metadata_object CHECK (jsonb_typeof(metadata) = 'object');
+
+ALTER TABLE transactions
+ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}',
+ADD COLUMN external_reference TEXT,
+ADD CONSTRAINT transaction_metadata_object CHECK (jsonb_typeof(metadata) = 'object');
+
+CREATE INDEX idx_accounts_business_external_reference
+    ON accounts (business_id, external_reference)
+    WHERE external_reference IS NOT NULL;
+
+CREATE INDEX idx_accounts_metadata
+    ON accounts USING GIN (metadata jsonb_path_ops);
+
+CREATE INDEX idx_transactions_business_external_reference
+    ON transactions (business_id, external_reference)
+    WHERE external_reference IS NOT NULL;
+
+CREATE INDEX idx_transactions_metadata
+    ON transactions USING GIN (metadata jsonb_path_ops);
@@ -0,0 +1,15 @@
+-- Free-text description on any transaction, and the external party of a credit (who sent the
+-- money) or a debit (who received it). Transfers have no external side.
+
+ALTER TABLE transactions
+ADD COLUMN description TEXT,
+ADD COLUMN counterparty_name TEXT,
+ADD COLUMN counterparty_account_identifier TEXT,
+ADD COLUMN counterparty_reference TEXT,
+ADD CONSTRAINT counterparty_has_name CHECK (
+    counterparty_name IS NOT NULL
+    OR (counterparty_account_identifier IS NULL AND counterparty_reference IS NULL)
+),
+ADD CONSTRAINT counterparty_external_side_only CHECK (
+    type <> 'transfer' OR counterparty_name IS NULL
+);
@@ -0,0 +1,15 @@
+-- end-of-day balances per customer account, filled by the snapshot worker.
+-- `balance` is the sum of the account's postings created before midnight UTC ending `day`.
+-- point-in-time queries start from the latest snapshot and add the postings after it.
+
+CREATE TABLE account_balance_snapshots (
+    account_id  UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    day         DATE NOT NULL,
+    balance     BIGINT NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (account_id, day)
+);
+
+CREATE INDEX idx_postings_account_created_at
+    ON postings (account_id, created_at);
@@ -0,0 +1,22 @@
+-- per-account overdraft: the balance may go down to -overdraft_limit (0 = no overdraft).
+-- the limit is changed through the API only, and every change is recorded in
+-- account_overdraft_limit_changes with the API key that made it.
+
+ALTER TABLE accounts
+ADD COLUMN overdraft_limit BIGINT NOT NULL DEFAULT 0,
+ADD CONSTRAINT overdraft_limit_non_negative CHECK (overdraft_limit >= 0),
+DROP CONSTRAINT balance_non_negative,
+ADD CONSTRAINT balance_within_overdraft_limit CHECK (balance >= -overdraft_limit);
+
+CREATE TABLE account_overdraft_limit_changes (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_id     UUID NOT NULL REFERENCES businesses(id) ON DELETE CASCADE,
+    account_id      UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    previous_limit  BIGINT NOT NULL,
+    new_limit       BIGINT NOT NULL,
+    api_key_id      UUID NOT NULL REFERENCES api_keys(id),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_account_overdraft_limit_changes_account
+    ON account_overdraft_limit_changes (account_id, created_at DESC);
@@ -0,0 +1,14 @@
+-- per-account risk limits on money leaving the account (debits and outgoing transfers).
+-- NULL = no limit. Daily limits count the account's outgoing postings since midnight UTC.
+
+ALTER TABLE accounts
+ADD COLUMN max_transaction_amount BIGINT,
+ADD COLUMN max_daily_outflow_count BIGINT,
+ADD COLUMN max_daily_outflow_amount BIGINT,
+ADD COLUMN min_balance BIGINT,
+ADD CONSTRAINT account_limits_valid CHECK (
+    (max_transaction_amount IS NULL OR max_transaction_amount > 0) AND
+    (max_daily_outflow_count IS NULL OR max_daily_outflow_count > 0) AND
+    (max_daily_outflow_amount IS NULL OR max_daily_outflow_amount > 0) AND
+    (min_balance IS NULL OR min_balance >= 0)
+);
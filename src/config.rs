@@ -7,6 +7,8 @@ pub struct Config {
     pub database_url: String,
     pub server_port: u16,
     pub hmac_secret: String,
+    // Older HMAC secrets still accepted for API key lookup during a rotation.
+    pub previous_hmac_secrets: Vec<String>,
 }
 
 impl Config {
@@ -24,10 +26,20 @@ impl Config {
         let hmac_secret = env::var("HMAC_SECRET")
             .expect("HMAC_SECRET must be set");
 
+        // Comma-separated; empty entries are ignored.
+        let previous_hmac_secrets = env::var("HMAC_PREVIOUS_SECRETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+
         Self {
             database_url,
             server_port,
             hmac_secret,
+            previous_hmac_secrets,
         }
     }
 }
@@ -0,0 +1,129 @@
+// ISO 4217 currencies and their minor-unit exponents.
+//
+// Amounts are stored as i64 minor units everywhere; the exponent says where the decimal point
+// goes (JPY 1000 = "1000", USD 1000 = "10.00", KWD 1000 = "1.000").
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    pub code: &'static str,
+    pub exponent: u32,
+}
+
+const fn c(code: &'static str, exponent: u32) -> Currency {
+    Currency { code, exponent }
+}
+
+// Active codes with a defined minor unit. Funds and units of account (BOV, CHE, CLF, UYW, ...)
+// and precious metals (XAU, XDR, ...) are omitted: they are not spendable currencies.
+// Sorted by code for binary search.
+#[rustfmt::skip]
+static CURRENCIES: &[Currency] = &[
+    c("AED", 2), c("AFN", 2), c("ALL", 2), c("AMD", 2), c("ANG", 2), c("AOA", 2), c("ARS", 2),
+    c("AUD", 2), c("AWG", 2), c("AZN", 2), c("BAM", 2), c("BBD", 2), c("BDT", 2), c("BGN", 2),
+    c("BHD", 3), c("BIF", 0), c("BMD", 2), c("BND", 2), c("BOB", 2), c("BRL", 2), c("BSD", 2),
+    c("BTN", 2), c("BWP", 2), c("BYN", 2), c("BZD", 2), c("CAD", 2), c("CDF", 2), c("CHF", 2),
+    c("CLP", 0), c("CNY", 2), c("COP", 2), c("CRC", 2), c("CUP", 2), c("CVE", 2), c("CZK", 2),
+    c("DJF", 0), c("DKK", 2), c("DOP", 2), c("DZD", 2), c("EGP", 2), c("ERN", 2), c("ETB", 2),
+    c("EUR", 2), c("FJD", 2), c("FKP", 2), c("GBP", 2), c("GEL", 2), c("GHS", 2), c("GIP", 2),
+    c("GMD", 2), c("GNF", 0), c("GTQ", 2), c("GYD", 2), c("HKD", 2), c("HNL", 2), c("HTG", 2),
+    c("HUF", 2), c("IDR", 2), c("ILS", 2), c("INR", 2), c("IQD", 3), c("IRR", 2), c("ISK", 0),
+    c("JMD", 2), c("JOD", 3), c("JPY", 0), c("KES", 2), c("KGS", 2), c("KHR", 2), c("KMF", 0),
+    c("KPW", 2), c("KRW", 0), c("KWD", 3), c("KYD", 2), c("KZT", 2), c("LAK", 2), c("LBP", 2),
+    c("LKR", 2), c("LRD", 2), c("LSL", 2), c("LYD", 3), c("MAD", 2), c("MDL", 2), c("MGA", 2),
+    c("MKD", 2), c("MMK", 2), c("MNT", 2), c("MOP", 2), c("MRU", 2), c("MUR", 2), c("MVR", 2),
+    c("MWK", 2), c("MXN", 2), c("MYR", 2), c("MZN", 2), c("NAD", 2), c("NGN", 2), c("NIO", 2),
+    c("NOK", 2), c("NPR", 2), c("NZD", 2), c("OMR", 3), c("PAB", 2), c("PEN", 2), c("PGK", 2),
+    c("PHP", 2), c("PKR", 2), c("PLN", 2), c("PYG", 0), c("QAR", 2), c("RON", 2), c("RSD", 2),
+    c("RUB", 2), c("RWF", 0), c("SAR", 2), c("SBD", 2), c("SCR", 2), c("SDG", 2), c("SEK", 2),
+    c("SGD", 2), c("SHP", 2), c("SLE", 2), c("SOS", 2), c("SRD", 2), c("SSP", 2), c("STN", 2),
+    c("SVC", 2), c("SYP", 2), c("SZL", 2), c("THB", 2), c("TJS", 2), c("TMT", 2), c("TND", 3),
+    c("TOP", 2), c("TRY", 2), c("TTD", 2), c("TWD", 2), c("TZS", 2), c("UAH", 2), c("UGX", 0),
+    c("USD", 2), c("UYU", 2), c("UZS", 2), c("VED", 2), c("VES", 2), c("VND", 0), c("VUV", 0),
+    c("WST", 2), c("XAF", 0), c("XCD", 2), c("XCG", 2), c("XOF", 0), c("XPF", 0), c("YER", 2),
+    c("ZAR", 2), c("ZMW", 2), c("ZWG", 2),
+];
+
+/// Looks up an ISO 4217 code. Accounts stored as CHAR(3) may carry padding, so input is trimmed;
+/// matching is case-sensitive (codes are stored uppercase).
+pub fn find(code: &str) -> Option<Currency> {
+    let code = code.trim();
+    CURRENCIES
+        .binary_search_by(|c| c.code.cmp(code))
+        .ok()
+        .map(|i| CURRENCIES[i])
+}
+
+/// Formats minor units as a plain decimal string using the currency exponent, e.g.
+/// `format_minor_units(-1050, 2) == "-10.50"`.
+pub fn format_minor_units(amount: i64, exponent: u32) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let abs = amount.unsigned_abs();
+    if exponent == 0 {
+        return format!("{sign}{abs}");
+    }
+
+    let scale = 10u64.pow(exponent);
+    let width = exponent as usize;
+    format!("{sign}{}.{:0width$}", abs / scale, abs % scale)
+}
+
+/// Formatted amount for a currency code, or None for codes outside the registry
+/// (accounts created before currencies were validated).
+pub fn format_amount(amount: i64, code: &str) -> Option<String> {
+    find(code).map(|c| format_minor_units(amount, c.exponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_sorted_and_unique() {
+        assert!(CURRENCIES.windows(2).all(|w| w[0].code < w[1].code));
+    }
+
+    #[test]
+    fn find_trims_padding_and_is_case_sensitive() {
+        assert_eq!(find("USD"), Some(c("USD", 2)));
+        assert_eq!(find(" JPY "), Some(c("JPY", 0)));
+        assert_eq!(find("KWD\t"), Some(c("KWD", 3)));
+        assert_eq!(find("usd"), None);
+        assert_eq!(find("Usd"), None);
+    }
+
+    #[test]
+    fn find_rejects_unknown_codes_and_funds() {
+        for code in ["", "ZZZ", "US", "USDD", "XAU", "XDR", "CLF", "UYW", "USN", "BOV"] {
+            assert_eq!(find(code), None, "{code:?}");
+        }
+    }
+
+    #[test]
+    fn formats_by_exponent() {
+        assert_eq!(format_minor_units(1050, 0), "1050");
+        assert_eq!(format_minor_units(1050, 2), "10.50");
+        assert_eq!(format_minor_units(1050, 3), "1.050");
+        assert_eq!(format_minor_units(1050, 4), "0.1050");
+        assert_eq!(format_minor_units(0, 0), "0");
+        assert_eq!(format_minor_units(0, 2), "0.00");
+    }
+
+    #[test]
+    fn formats_negative_and_sub_unit_amounts() {
+        assert_eq!(format_minor_units(-5, 2), "-0.05");
+        assert_eq!(format_minor_units(5, 2), "0.05");
+        assert_eq!(format_minor_units(-1050, 2), "-10.50");
+        assert_eq!(format_minor_units(-7, 0), "-7");
+        assert_eq!(format_minor_units(-1, 3), "-0.001");
+        assert_eq!(format_minor_units(-5, 4), "-0.0005");
+        assert_eq!(format_minor_units(i64::MIN, 2), "-92233720368547758.08");
+    }
+
+    #[test]
+    fn format_amount_uses_the_currency_exponent() {
+        assert_eq!(format_amount(1050, "JPY").as_deref(), Some("1050"));
+        assert_eq!(format_amount(-5, "USD").as_deref(), Some("-0.05"));
+        assert_eq!(format_amount(1050, "KWD ").as_deref(), Some("1.050"));
+        assert_eq!(format_amount(1050, "ZZZ"), None);
+    }
+}
@@ -2,202 +2,1810 @@ pub mod pool;
 
 use uuid::Uuid;
 
-use sqlx::PgPool;
-use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use chrono::{DateTime, NaiveDate, Utc};
 
-use crate::models::api_key::ApiKeyLookup;
-use crate::models::account::Account;
-use crate::models::transaction::{Transaction, TransactionType};
+use crate::models::api_key::{ApiKey, ApiKeyLookup, parse_scopes};
+use crate::metadata::Metadata;
+use crate::models::account::{
+    Account, AccountFilter, AccountLimits, AccountStatus, OverdraftLimitChange, StatementEntry,
+};
+use crate::models::fx::{FxConversion, FxQuote, FxRateEntry};
+use crate::models::hold::{Hold, HoldStatus};
+use crate::models::ledger::AccountLedgerBalance;
+use crate::models::pagination::{Cursor, SeqCursor};
+use crate::models::scheduled_transaction::{
+    CreateScheduledTransactionRequest, ScheduleFrequency, ScheduleStatus, ScheduledRunStatus,
+    ScheduledTransaction, ScheduledTransactionRun,
+};
+use crate::models::transaction::{
+    Counterparty, Transaction, TransactionFilter, TransactionType,
+};
 use crate::models::webhook::WebhookEndpoint;
 
+// (id, business_id, hmac_secret_fingerprint, scopes, allowed_account_ids)
+type ApiKeyLookupRow = (Uuid, Uuid, Option<String>, Vec<String>, Option<Vec<Uuid>>);
+
 pub async fn find_active_api_key_by_hash(
     pool: &PgPool,
-    key_hash: &str,
-) -> Result<Option<ApiKeyLookup>, sqlx::Error> {
-    let q = "SELECT id, business_id FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL";
+    key_hash: &str,
+) -> Result<Option<ApiKeyLookup>, sqlx::Error> {
+    // revoked_at in the future means "rotated, still inside the overlap period".
+    let q = r#"
+        SELECT id, business_id, hmac_secret_fingerprint, scopes, allowed_account_ids
+        FROM api_keys
+        WHERE key_hash = $1 AND (revoked_at IS NULL OR revoked_at > now())
+    "#;
+
+    let row: Option<ApiKeyLookupRow> = sqlx::query_as(q)
+        .bind(key_hash)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(
+        |(id, business_id, hmac_secret_fingerprint, scopes, allowed_account_ids)| ApiKeyLookup {
+            id,
+            business_id,
+            hmac_secret_fingerprint,
+            scopes: parse_scopes(&scopes),
+            allowed_account_ids,
+        },
+    ))
+}
+
+pub async fn rehash_api_key(
+    pool: &PgPool,
+    api_key_id: Uuid,
+    key_hash: &str,
+    hmac_secret_fingerprint: &str,
+) -> Result<(), sqlx::Error> {
+    let q = r#"
+        UPDATE api_keys
+        SET key_hash = $2, hmac_secret_fingerprint = $3
+        WHERE id = $1
+    "#;
+    sqlx::query(q)
+        .bind(api_key_id)
+        .bind(key_hash)
+        .bind(hmac_secret_fingerprint)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Active keys grouped by the fingerprint of the secret their hash was derived with.
+pub async fn count_active_api_keys_by_secret(
+    pool: &PgPool,
+) -> Result<Vec<(Option<String>, i64)>, sqlx::Error> {
+    let q = r#"
+        SELECT hmac_secret_fingerprint, COUNT(*)
+        FROM api_keys
+        WHERE revoked_at IS NULL OR revoked_at > now()
+        GROUP BY hmac_secret_fingerprint
+    "#;
+    sqlx::query_as(q).fetch_all(pool).await
+}
+
+pub async fn touch_api_key_last_used(pool: &PgPool, api_key_id: Uuid) -> Result<(), sqlx::Error> {
+    // Throttled to one write per key per minute.
+    let q = r#"
+        UPDATE api_keys
+        SET last_used_at = now()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')
+    "#;
+    sqlx::query(q).bind(api_key_id).execute(pool).await?;
+    Ok(())
+}
+
+// (id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes, allowed_account_ids)
+type ApiKeyRow = (
+    Uuid,
+    Uuid,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    Vec<String>,
+    Option<Vec<Uuid>>,
+);
+
+fn api_key_from_row(row: ApiKeyRow) -> ApiKey {
+    let (id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes, allowed) = row;
+    ApiKey {
+        id,
+        business_id,
+        key_prefix,
+        created_at,
+        last_used_at,
+        revoked_at,
+        scopes,
+        allowed_account_ids: allowed,
+    }
+}
+
+pub async fn create_api_key(
+    pool: &PgPool,
+    business_id: Uuid,
+    key_hash: &str,
+    key_prefix: &str,
+    hmac_secret_fingerprint: &str,
+    scopes: &[String],
+    allowed_account_ids: Option<&[Uuid]>,
+) -> Result<ApiKey, sqlx::Error> {
+    let q = r#"
+        INSERT INTO api_keys (
+            business_id, key_hash, key_prefix, hmac_secret_fingerprint, scopes, allowed_account_ids
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING
+            id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes,
+            allowed_account_ids
+    "#;
+
+    let row: ApiKeyRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(key_hash)
+        .bind(key_prefix)
+        .bind(hmac_secret_fingerprint)
+        .bind(scopes)
+        .bind(allowed_account_ids)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(api_key_from_row(row))
+}
+
+pub async fn list_api_keys(pool: &PgPool, business_id: Uuid) -> Result<Vec<ApiKey>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes,
+            allowed_account_ids
+        FROM api_keys
+        WHERE business_id = $1
+        ORDER BY created_at DESC
+    "#;
+
+    let rows: Vec<ApiKeyRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(api_key_from_row).collect())
+}
+
+// Any status, including revoked keys.
+pub async fn get_api_key(
+    pool: &PgPool,
+    business_id: Uuid,
+    api_key_id: Uuid,
+) -> Result<Option<ApiKey>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes,
+            allowed_account_ids
+        FROM api_keys
+        WHERE id = $1 AND business_id = $2
+    "#;
+
+    let row: Option<ApiKeyRow> = sqlx::query_as(q)
+        .bind(api_key_id)
+        .bind(business_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(api_key_from_row))
+}
+
+pub async fn revoke_api_key(
+    pool: &PgPool,
+    business_id: Uuid,
+    api_key_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    // Also cuts short the overlap period of a rotated key.
+    let q = r#"
+        UPDATE api_keys
+        SET revoked_at = now()
+        WHERE id = $1 AND business_id = $2 AND (revoked_at IS NULL OR revoked_at > now())
+    "#;
+
+    let result = sqlx::query(q)
+        .bind(api_key_id)
+        .bind(business_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Returns (new key, old key), or None if the old key is not active (or already rotated).
+// The new key inherits the old key's scopes and account allowlist.
+pub async fn rotate_api_key(
+    pool: &PgPool,
+    business_id: Uuid,
+    api_key_id: Uuid,
+    new_key_hash: &str,
+    new_key_prefix: &str,
+    hmac_secret_fingerprint: &str,
+    overlap_seconds: i64,
+) -> Result<Option<(ApiKey, ApiKey)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let q = r#"
+        UPDATE api_keys
+        SET revoked_at = now() + make_interval(secs => $3)
+        WHERE id = $1 AND business_id = $2 AND revoked_at IS NULL
+        RETURNING
+            id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes,
+            allowed_account_ids
+    "#;
+
+    let old: Option<ApiKeyRow> = sqlx::query_as(q)
+        .bind(api_key_id)
+        .bind(business_id)
+        .bind(overlap_seconds as f64)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let Some(old) = old else {
+        return Ok(None);
+    };
+
+    let q = r#"
+        INSERT INTO api_keys (
+            business_id, key_hash, key_prefix, hmac_secret_fingerprint, scopes, allowed_account_ids
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING
+            id, business_id, key_prefix, created_at, last_used_at, revoked_at, scopes,
+            allowed_account_ids
+    "#;
+
+    let new: ApiKeyRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(new_key_hash)
+        .bind(new_key_prefix)
+        .bind(hmac_secret_fingerprint)
+        .bind(&old.6)
+        .bind(&old.7)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Some((api_key_from_row(new), api_key_from_row(old))))
+}
+
+#[derive(sqlx::FromRow)]
+struct AccountRow {
+    id: Uuid,
+    business_id: Uuid,
+    name: String,
+    currency: String,
+    balance: i64,
+    available_balance: i64,
+    status: String,
+    status_changed_at: Option<DateTime<Utc>>,
+    metadata: String,
+    external_reference: Option<String>,
+    overdraft_limit: i64,
+    max_transaction_amount: Option<i64>,
+    max_daily_outflow_count: Option<i64>,
+    max_daily_outflow_amount: Option<i64>,
+    min_balance: Option<i64>,
+}
+
+fn account_from_row(row: AccountRow) -> Option<Account> {
+    Some(Account {
+        id: row.id,
+        business_id: row.business_id,
+        name: row.name,
+        currency: row.currency,
+        balance: row.balance,
+        available_balance: row.available_balance,
+        status: AccountStatus::from_db_str(&row.status)?,
+        status_changed_at: row.status_changed_at,
+        metadata: serde_json::from_str(&row.metadata).ok()?,
+        external_reference: row.external_reference,
+        overdraft_limit: row.overdraft_limit,
+        limits: AccountLimits {
+            max_transaction_amount: row.max_transaction_amount,
+            max_daily_outflow_count: row.max_daily_outflow_count,
+            max_daily_outflow_amount: row.max_daily_outflow_amount,
+            min_balance: row.min_balance,
+        },
+    })
+}
+
+pub async fn create_account(
+    pool: &PgPool,
+    business_id: Uuid,
+    name: &str,
+    currency: &str,
+    metadata: &Metadata,
+    external_reference: Option<&str>,
+) -> Result<Option<Account>, sqlx::Error> {
+    let metadata_json = serde_json::to_string(metadata).unwrap_or_else(|_| "{}".to_string());
+
+    let q = r#"
+        INSERT INTO accounts (business_id, name, currency, balance, metadata, external_reference)
+        VALUES ($1, $2, $3, 0, $4::jsonb, $5)
+        RETURNING
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+    "#;
+
+    let row: AccountRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(name)
+        .bind(currency)
+        .bind(metadata_json)
+        .bind(external_reference)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(account_from_row(row))
+}
+
+pub async fn list_accounts(
+    pool: &PgPool,
+    business_id: Uuid,
+    filter: &AccountFilter,
+) -> Result<Vec<Account>, sqlx::Error> {
+    let metadata_json = filter.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok());
+
+    let q = r#"
+        SELECT
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+        FROM accounts
+        WHERE business_id = $1 AND NOT is_system
+            AND ($2::uuid[] IS NULL OR id = ANY($2))
+            AND ($3::text IS NULL OR external_reference = $3)
+            AND ($4::jsonb IS NULL OR metadata @> $4::jsonb)
+        ORDER BY created_at DESC
+    "#;
+
+    let rows: Vec<AccountRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(filter.allowed_account_ids.as_deref())
+        .bind(filter.external_reference.as_deref())
+        .bind(metadata_json)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().filter_map(account_from_row).collect())
+}
+
+pub async fn get_account(
+    pool: &PgPool,
+    business_id: Uuid,
+    account_id: Uuid,
+) -> Result<Option<Account>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+        FROM accounts
+        WHERE id = $1 AND business_id = $2 AND NOT is_system
+        LIMIT 1
+    "#;
+
+    let row: Option<AccountRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(business_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.and_then(account_from_row))
+}
+
+// Same row lock as money movements, so a status or overdraft limit change waits for in-flight
+// transactions on the account and later ones see the new value.
+pub async fn lock_account_for_change<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    account_id: Uuid,
+) -> Result<Option<Account>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+        FROM accounts
+        WHERE id = $1 AND business_id = $2 AND NOT is_system
+        FOR UPDATE
+    "#;
+
+    let row: Option<AccountRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(business_id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(account_from_row))
+}
+
+pub async fn set_account_status<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+    status: AccountStatus,
+) -> Result<Option<Account>, sqlx::Error> {
+    let q = r#"
+        UPDATE accounts
+        SET status = $2::account_status, status_changed_at = now()
+        WHERE id = $1
+        RETURNING
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+    "#;
+
+    let row: Option<AccountRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(status.as_db_str())
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(account_from_row))
+}
+
+pub async fn set_account_overdraft_limit<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+    overdraft_limit: i64,
+) -> Result<Option<Account>, sqlx::Error> {
+    let q = r#"
+        UPDATE accounts
+        SET overdraft_limit = $2
+        WHERE id = $1
+        RETURNING
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+    "#;
+
+    let row: Option<AccountRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(overdraft_limit)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(account_from_row))
+}
+
+pub async fn set_account_limits<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+    limits: &AccountLimits,
+) -> Result<Option<Account>, sqlx::Error> {
+    let q = r#"
+        UPDATE accounts
+        SET
+            max_transaction_amount = $2,
+            max_daily_outflow_count = $3,
+            max_daily_outflow_amount = $4,
+            min_balance = $5
+        WHERE id = $1
+        RETURNING
+            id, business_id, name, currency, balance, balance - held_amount AS available_balance,
+            status::text AS status, status_changed_at, metadata::text AS metadata,
+            external_reference, overdraft_limit, max_transaction_amount,
+            max_daily_outflow_count, max_daily_outflow_amount, min_balance
+    "#;
+
+    let row: Option<AccountRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(limits.max_transaction_amount)
+        .bind(limits.max_daily_outflow_count)
+        .bind(limits.max_daily_outflow_amount)
+        .bind(limits.min_balance)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(account_from_row))
+}
+
+// Number and total of the account's outgoing postings since midnight UTC.
+pub async fn daily_outflow<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+) -> Result<(i64, i64), sqlx::Error> {
+    let q = r#"
+        SELECT COUNT(*), COALESCE(-SUM(amount), 0)::bigint
+        FROM postings
+        WHERE account_id = $1
+            AND amount < 0
+            AND created_at >= date_trunc('day', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+    "#;
+
+    sqlx::query_as(q).bind(account_id).fetch_one(executor).await
+}
+
+// (id, account_id, previous_limit, new_limit, api_key_id, created_at)
+type OverdraftLimitChangeRow = (Uuid, Uuid, i64, i64, Uuid, DateTime<Utc>);
+
+fn overdraft_limit_change_from_row(row: OverdraftLimitChangeRow) -> OverdraftLimitChange {
+    let (id, account_id, previous_limit, new_limit, api_key_id, created_at) = row;
+    OverdraftLimitChange {
+        id,
+        account_id,
+        previous_limit,
+        new_limit,
+        api_key_id,
+        created_at,
+    }
+}
+
+pub async fn insert_overdraft_limit_change<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    account_id: Uuid,
+    previous_limit: i64,
+    new_limit: i64,
+    api_key_id: Uuid,
+) -> Result<OverdraftLimitChange, sqlx::Error> {
+    let q = r#"
+        INSERT INTO account_overdraft_limit_changes (
+            business_id, account_id, previous_limit, new_limit, api_key_id
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, account_id, previous_limit, new_limit, api_key_id, created_at
+    "#;
+
+    let row: OverdraftLimitChangeRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(account_id)
+        .bind(previous_limit)
+        .bind(new_limit)
+        .bind(api_key_id)
+        .fetch_one(executor)
+        .await?;
+
+    Ok(overdraft_limit_change_from_row(row))
+}
+
+// Newest first.
+pub async fn list_overdraft_limit_changes(
+    pool: &PgPool,
+    business_id: Uuid,
+    account_id: Uuid,
+) -> Result<Vec<OverdraftLimitChange>, sqlx::Error> {
+    let q = r#"
+        SELECT id, account_id, previous_limit, new_limit, api_key_id, created_at
+        FROM account_overdraft_limit_changes
+        WHERE business_id = $1 AND account_id = $2
+        ORDER BY created_at DESC, id DESC
+    "#;
+
+    let rows: Vec<OverdraftLimitChangeRow> =
+        sqlx::query_as(q).bind(business_id).bind(account_id).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(overdraft_limit_change_from_row).collect())
+}
+
+pub async fn count_accounts_in_business(
+    pool: &PgPool,
+    business_id: Uuid,
+    account_ids: &[Uuid],
+) -> Result<i64, sqlx::Error> {
+    let q = r#"
+        SELECT COUNT(*)
+        FROM accounts
+        WHERE business_id = $1 AND NOT is_system AND id = ANY($2)
+    "#;
+
+    let (count,): (i64,) = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(account_ids)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
+// Restricted keys (allowed_account_ids is Some) only see their own accounts, not system accounts.
+pub async fn list_account_ledger_balances(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+) -> Result<Vec<AccountLedgerBalance>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            a.id,
+            a.name,
+            a.currency,
+            a.is_system,
+            a.balance,
+            COALESCE(SUM(p.amount), 0)::BIGINT AS ledger_balance
+        FROM accounts a
+        LEFT JOIN postings p ON p.account_id = a.id
+        WHERE a.business_id = $1
+            AND ($2::uuid[] IS NULL OR a.id = ANY($2))
+        GROUP BY a.id
+        ORDER BY a.is_system, a.created_at
+    "#;
+
+    let rows: Vec<(Uuid, String, String, bool, i64, i64)> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(allowed_account_ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(account_id, name, currency, is_system, cached_balance, ledger_balance)| {
+                AccountLedgerBalance {
+                    account_id,
+                    name,
+                    currency,
+                    is_system,
+                    cached_balance,
+                    ledger_balance,
+                }
+            },
+        )
+        .collect())
+}
+
+// Columns selected by every transaction query, in this shape.
+#[derive(sqlx::FromRow)]
+struct TransactionRow {
+    id: Uuid,
+    tx_type: String,
+    source_account_id: Option<Uuid>,
+    dest_account_id: Option<Uuid>,
+    amount: i64,
+    currency: String,
+    dest_amount: Option<i64>,
+    dest_currency: Option<String>,
+    fx_rate: Option<String>,
+    reversal_of: Option<Uuid>,
+    reversed_amount: i64,
+    metadata: String,
+    external_reference: Option<String>,
+    description: Option<String>,
+    counterparty_name: Option<String>,
+    counterparty_account_identifier: Option<String>,
+    counterparty_reference: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+fn transaction_from_row(business_id: Uuid, row: TransactionRow) -> Option<Transaction> {
+    let tx_type = TransactionType::from_db_str(&row.tx_type)?;
+
+    let fx = match (row.fx_rate, row.dest_amount, row.dest_currency) {
+        (Some(rate), Some(dest_amount), Some(dest_currency)) => Some(FxConversion {
+            rate,
+            dest_amount,
+            dest_currency,
+        }),
+        _ => None,
+    };
+
+    let counterparty = row.counterparty_name.map(|name| Counterparty {
+        name,
+        account_identifier: row.counterparty_account_identifier,
+        reference: row.counterparty_reference,
+    });
+
+    Some(Transaction {
+        id: row.id,
+        business_id,
+        tx_type,
+        source_account_id: row.source_account_id,
+        dest_account_id: row.dest_account_id,
+        amount: row.amount,
+        currency: row.currency,
+        fx,
+        reversal_of: row.reversal_of,
+        reversed_amount: row.reversed_amount,
+        metadata: serde_json::from_str(&row.metadata).ok()?,
+        external_reference: row.external_reference,
+        description: row.description,
+        counterparty,
+        created_at: row.created_at,
+    })
+}
+
+pub async fn list_transactions(
+    pool: &PgPool,
+    business_id: Uuid,
+    filter: &TransactionFilter,
+    cursor: Option<Cursor>,
+    limit: i64,
+) -> Result<Vec<Transaction>, sqlx::Error> {
+    let metadata_json = filter.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok());
+
+    let q = r#"
+        SELECT
+            id, type::text AS tx_type, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(fx_rate)::text AS fx_rate, reversal_of,
+            reversed_amount, metadata::text AS metadata, external_reference, description,
+            counterparty_name, counterparty_account_identifier, counterparty_reference, created_at
+        FROM transactions
+        WHERE business_id = $1
+            AND ($2::transaction_type IS NULL OR type = $2::transaction_type)
+            AND ($3::uuid IS NULL OR source_account_id = $3 OR dest_account_id = $3)
+            AND ($4::bigint IS NULL OR amount >= $4)
+            AND ($5::bigint IS NULL OR amount <= $5)
+            AND ($6::timestamptz IS NULL OR created_at >= $6)
+            AND ($7::timestamptz IS NULL OR created_at < $7)
+            AND ($8::timestamptz IS NULL OR (created_at, id) < ($8, $9::uuid))
+            AND (
+                $11::uuid[] IS NULL
+                OR source_account_id = ANY($11)
+                OR dest_account_id = ANY($11)
+            )
+            AND ($12::text IS NULL OR external_reference = $12)
+            AND ($13::jsonb IS NULL OR metadata @> $13::jsonb)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $10
+    "#;
+
+    let rows: Vec<TransactionRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(filter.tx_type.map(TransactionType::as_db_str))
+        .bind(filter.account_id)
+        .bind(filter.min_amount)
+        .bind(filter.max_amount)
+        .bind(filter.created_from)
+        .bind(filter.created_to)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit)
+        .bind(filter.allowed_account_ids.as_deref())
+        .bind(filter.external_reference.as_deref())
+        .bind(metadata_json)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| transaction_from_row(business_id, row))
+        .collect())
+}
+
+#[derive(sqlx::FromRow)]
+struct StatementRow {
+    seq: i64,
+    posting_amount: i64,
+    balance_after: Option<i64>,
+    #[sqlx(flatten)]
+    transaction: TransactionRow,
+}
+
+pub async fn list_account_statement(
+    pool: &PgPool,
+    business_id: Uuid,
+    account_id: Uuid,
+    cursor: Option<SeqCursor>,
+    limit: i64,
+) -> Result<Vec<StatementEntry>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            p.seq,
+            p.amount AS posting_amount,
+            p.balance_after,
+            t.id, t.type::text AS tx_type, t.source_account_id, t.dest_account_id,
+            t.amount, t.currency, t.dest_amount, t.dest_currency,
+            trim_scale(t.fx_rate)::text AS fx_rate, t.reversal_of, t.reversed_amount,
+            t.metadata::text AS metadata, t.external_reference, t.description,
+            t.counterparty_name, t.counterparty_account_identifier, t.counterparty_reference,
+            t.created_at
+        FROM postings p
+        JOIN journal_entries je ON je.id = p.journal_entry_id
+        JOIN transactions t ON t.id = je.transaction_id
+        WHERE p.account_id = $1
+            AND je.business_id = $2
+            AND ($3::bigint IS NULL OR p.seq < $3)
+        ORDER BY p.seq DESC
+        LIMIT $4
+    "#;
+
+    let rows: Vec<StatementRow> = sqlx::query_as(q)
+        .bind(account_id)
+        .bind(business_id)
+        .bind(cursor.map(|c| c.0))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let transaction = transaction_from_row(business_id, row.transaction)?;
+            Some(StatementEntry {
+                seq: row.seq,
+                transaction,
+                amount: row.posting_amount,
+                // Only NULL for system accounts, which are not exposed here.
+                balance_after: row.balance_after?,
+            })
+        })
+        .collect())
+}
+
+// Sum of the account's postings created before `at`: the latest snapshot ending at or before
+// `at`, plus the postings after it.
+pub async fn account_balance_at(
+    pool: &PgPool,
+    account_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    let q = r#"
+        WITH base AS (
+            SELECT day, balance
+            FROM account_balance_snapshots
+            WHERE account_id = $1 AND day < ($2 AT TIME ZONE 'UTC')::date
+            ORDER BY day DESC
+            LIMIT 1
+        )
+        SELECT
+            COALESCE((SELECT balance FROM base), 0)
+            + COALESCE((
+                SELECT SUM(amount)::bigint
+                FROM postings
+                WHERE account_id = $1
+                    AND created_at < $2
+                    AND created_at >= COALESCE(
+                        ((SELECT day FROM base) + 1)::timestamp AT TIME ZONE 'UTC',
+                        '-infinity'
+                    )
+            ), 0)
+    "#;
+
+    sqlx::query_scalar(q).bind(account_id).bind(at).fetch_one(pool).await
+}
+
+pub async fn list_balance_snapshots(
+    pool: &PgPool,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(NaiveDate, i64)>, sqlx::Error> {
+    let q = r#"
+        SELECT day, balance
+        FROM account_balance_snapshots
+        WHERE account_id = $1 AND day BETWEEN $2 AND $3
+        ORDER BY day
+    "#;
+
+    sqlx::query_as(q).bind(account_id).bind(from).bind(to).fetch_all(pool).await
+}
+
+// Net postings per UTC day, for days from `from` through `to` that have any.
+pub async fn daily_posting_totals(
+    pool: &PgPool,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(NaiveDate, i64)>, sqlx::Error> {
+    let q = r#"
+        SELECT (created_at AT TIME ZONE 'UTC')::date AS day, SUM(amount)::bigint
+        FROM postings
+        WHERE account_id = $1
+            AND created_at >= $2::date::timestamp AT TIME ZONE 'UTC'
+            AND created_at < ($3::date + 1)::timestamp AT TIME ZONE 'UTC'
+        GROUP BY day
+        ORDER BY day
+    "#;
+
+    sqlx::query_as(q).bind(account_id).bind(from).bind(to).fetch_all(pool).await
+}
+
+// Writes the next missing end-of-day snapshot of up to `limit` customer accounts: the day after
+// their latest snapshot, or the day they were created. Only days that ended more than
+// `settle_secs` ago are snapshotted, so transactions still in flight at midnight are included.
+// Closed accounts stop after the day they were closed. Returns the number of rows written.
+pub async fn insert_next_balance_snapshots(
+    pool: &PgPool,
+    settle_secs: i64,
+    limit: i64,
+) -> Result<u64, sqlx::Error> {
+    let q = r#"
+        INSERT INTO account_balance_snapshots (account_id, day, balance)
+        SELECT
+            a.id,
+            n.day,
+            COALESCE(last.balance, 0) + COALESCE((
+                SELECT SUM(p.amount)::bigint
+                FROM postings p
+                WHERE p.account_id = a.id
+                    AND p.created_at >= n.day::timestamp AT TIME ZONE 'UTC'
+                    AND p.created_at < (n.day + 1)::timestamp AT TIME ZONE 'UTC'
+            ), 0)
+        FROM accounts a
+        LEFT JOIN LATERAL (
+            SELECT s.day, s.balance
+            FROM account_balance_snapshots s
+            WHERE s.account_id = a.id
+            ORDER BY s.day DESC
+            LIMIT 1
+        ) last ON true
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(last.day + 1, (a.created_at AT TIME ZONE 'UTC')::date) AS day
+        ) n
+        WHERE NOT a.is_system
+            AND n.day < ((now() - make_interval(secs => $1)) AT TIME ZONE 'UTC')::date
+            AND (
+                a.status <> 'closed'
+                OR n.day <= (a.status_changed_at AT TIME ZONE 'UTC')::date
+            )
+        LIMIT $2
+        ON CONFLICT (account_id, day) DO NOTHING
+    "#;
+
+    let result = sqlx::query(q).bind(settle_secs as f64).bind(limit).execute(pool).await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn get_transaction(
+    pool: &PgPool,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<Transaction>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, type::text AS tx_type, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(fx_rate)::text AS fx_rate, reversal_of,
+            reversed_amount, metadata::text AS metadata, external_reference, description,
+            counterparty_name, counterparty_account_identifier, counterparty_reference, created_at
+        FROM transactions
+        WHERE business_id = $1 AND id = $2
+        LIMIT 1
+    "#;
+
+    let row: Option<TransactionRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.and_then(|row| transaction_from_row(business_id, row)))
+}
+
+// Locks the transaction row for the rest of the DB transaction; reversals of the same
+// transaction serialize on it.
+pub async fn lock_transaction_for_update<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<Transaction>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, type::text AS tx_type, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(fx_rate)::text AS fx_rate, reversal_of,
+            reversed_amount, metadata::text AS metadata, external_reference, description,
+            counterparty_name, counterparty_account_identifier, counterparty_reference, created_at
+        FROM transactions
+        WHERE business_id = $1 AND id = $2
+        FOR UPDATE
+    "#;
+
+    let row: Option<TransactionRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(|row| transaction_from_row(business_id, row)))
+}
+
+pub async fn add_reversed_amount<'e>(
+    executor: impl PgExecutor<'e>,
+    id: Uuid,
+    amount: i64,
+) -> Result<i64, sqlx::Error> {
+    let q = r#"
+        UPDATE transactions
+        SET reversed_amount = reversed_amount + $2
+        WHERE id = $1
+        RETURNING reversed_amount
+    "#;
+
+    sqlx::query_scalar(q)
+        .bind(id)
+        .bind(amount)
+        .fetch_one(executor)
+        .await
+}
+
+pub async fn list_fx_rates(
+    pool: &PgPool,
+    business_id: Uuid,
+) -> Result<Vec<FxRateEntry>, sqlx::Error> {
+    let q = r#"
+        SELECT base_currency, quote_currency, trim_scale(rate)::text, updated_at
+        FROM fx_rates
+        WHERE business_id = $1
+        ORDER BY base_currency, quote_currency
+    "#;
+
+    let rows: Vec<(String, String, String, DateTime<Utc>)> = sqlx::query_as(q)
+        .bind(business_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(base_currency, quote_currency, rate, updated_at)| FxRateEntry {
+            base_currency,
+            quote_currency,
+            rate,
+            updated_at,
+        })
+        .collect())
+}
+
+pub async fn upsert_fx_rate(
+    pool: &PgPool,
+    business_id: Uuid,
+    base_currency: &str,
+    quote_currency: &str,
+    rate: &str,
+) -> Result<FxRateEntry, sqlx::Error> {
+    let q = r#"
+        INSERT INTO fx_rates (business_id, base_currency, quote_currency, rate)
+        VALUES ($1, $2, $3, $4::numeric)
+        ON CONFLICT (business_id, base_currency, quote_currency)
+        DO UPDATE SET rate = EXCLUDED.rate, updated_at = now()
+        RETURNING base_currency, quote_currency, trim_scale(rate)::text, updated_at
+    "#;
+
+    let (base_currency, quote_currency, rate, updated_at): (String, String, String, DateTime<Utc>) =
+        sqlx::query_as(q)
+            .bind(business_id)
+            .bind(base_currency)
+            .bind(quote_currency)
+            .bind(rate)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(FxRateEntry {
+        base_currency,
+        quote_currency,
+        rate,
+        updated_at,
+    })
+}
+
+pub async fn delete_fx_rate(
+    pool: &PgPool,
+    business_id: Uuid,
+    base_currency: &str,
+    quote_currency: &str,
+) -> Result<bool, sqlx::Error> {
+    let q = r#"
+        DELETE FROM fx_rates
+        WHERE business_id = $1 AND base_currency = $2 AND quote_currency = $3
+    "#;
+
+    let result = sqlx::query(q)
+        .bind(business_id)
+        .bind(base_currency)
+        .bind(quote_currency)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Current table rate for base -> quote, as a decimal string.
+pub async fn find_fx_rate<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    base_currency: &str,
+    quote_currency: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let q = r#"
+        SELECT trim_scale(rate)::text
+        FROM fx_rates
+        WHERE business_id = $1 AND base_currency = $2 AND quote_currency = $3
+    "#;
+
+    let row: Option<(String,)> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(base_currency)
+        .bind(quote_currency)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.map(|(rate,)| rate))
+}
+
+#[derive(sqlx::FromRow)]
+struct FxQuoteRow {
+    id: Uuid,
+    business_id: Uuid,
+    source_account_id: Uuid,
+    dest_account_id: Uuid,
+    amount: i64,
+    currency: String,
+    dest_amount: i64,
+    dest_currency: String,
+    rate: String,
+    expires_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+    transaction_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<FxQuoteRow> for FxQuote {
+    fn from(row: FxQuoteRow) -> Self {
+        Self {
+            id: row.id,
+            business_id: row.business_id,
+            source_account_id: row.source_account_id,
+            dest_account_id: row.dest_account_id,
+            amount: row.amount,
+            currency: row.currency,
+            conversion: FxConversion {
+                rate: row.rate,
+                dest_amount: row.dest_amount,
+                dest_currency: row.dest_currency,
+            },
+            expires_at: row.expires_at,
+            used_at: row.used_at,
+            transaction_id: row.transaction_id,
+            created_at: row.created_at,
+        }
+    }
+}
+
+pub struct NewFxQuote<'a> {
+    pub source_account_id: Uuid,
+    pub dest_account_id: Uuid,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub conversion: &'a FxConversion,
+    pub ttl_secs: i64,
+}
+
+pub async fn create_fx_quote(
+    pool: &PgPool,
+    business_id: Uuid,
+    quote: &NewFxQuote<'_>,
+) -> Result<FxQuote, sqlx::Error> {
+    let q = r#"
+        INSERT INTO fx_quotes (
+            business_id, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, rate, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8::numeric, now() + make_interval(secs => $9))
+        RETURNING
+            id, business_id, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(rate)::text AS rate,
+            expires_at, used_at, transaction_id, created_at
+    "#;
 
-    let row: Option<(Uuid, Uuid)> = sqlx::query_as(q)
-        .bind(key_hash)
-        .fetch_optional(pool)
+    let row: FxQuoteRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(quote.source_account_id)
+        .bind(quote.dest_account_id)
+        .bind(quote.amount)
+        .bind(quote.currency.trim())
+        .bind(quote.conversion.dest_amount)
+        .bind(quote.conversion.dest_currency.trim())
+        .bind(&quote.conversion.rate)
+        .bind(quote.ttl_secs as f64)
+        .fetch_one(pool)
         .await?;
 
-    Ok(row.map(|(id, business_id)| ApiKeyLookup { id, business_id }))
+    Ok(row.into())
 }
 
-pub async fn create_account(
+pub async fn get_fx_quote(
     pool: &PgPool,
     business_id: Uuid,
-    name: &str,
-    currency: &str,
-) -> Result<Account, sqlx::Error> {
+    id: Uuid,
+) -> Result<Option<FxQuote>, sqlx::Error> {
     let q = r#"
-        INSERT INTO accounts (business_id, name, currency, balance)
-        VALUES ($1, $2, $3, 0)
-        RETURNING id, business_id, name, currency, balance
+        SELECT
+            id, business_id, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(rate)::text AS rate,
+            expires_at, used_at, transaction_id, created_at
+        FROM fx_quotes
+        WHERE business_id = $1 AND id = $2
     "#;
 
-    let row: (Uuid, Uuid, String, String, i64) = sqlx::query_as(q)
+    let row: Option<FxQuoteRow> = sqlx::query_as(q)
         .bind(business_id)
-        .bind(name)
-        .bind(currency)
-        .fetch_one(pool)
+        .bind(id)
+        .fetch_optional(pool)
         .await?;
 
-    Ok(Account {
-        id: row.0,
-        business_id: row.1,
-        name: row.2,
-        currency: row.3,
-        balance: row.4,
-    })
+    Ok(row.map(FxQuote::from))
 }
 
-pub async fn list_accounts(pool: &PgPool, business_id: Uuid) -> Result<Vec<Account>, sqlx::Error> {
+// Locks the quote row for the rest of the DB transaction, so two transfers cannot both consume
+// it. Also returns whether it has expired, judged by the DB clock like `expires_at` itself.
+pub async fn lock_fx_quote_for_update<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<(FxQuote, bool)>, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        #[sqlx(flatten)]
+        quote: FxQuoteRow,
+        expired: bool,
+    }
+
     let q = r#"
-        SELECT id, business_id, name, currency, balance
-        FROM accounts
-        WHERE business_id = $1
-        ORDER BY created_at DESC
+        SELECT
+            id, business_id, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, trim_scale(rate)::text AS rate,
+            expires_at, used_at, transaction_id, created_at,
+            expires_at <= now() AS expired
+        FROM fx_quotes
+        WHERE business_id = $1 AND id = $2
+        FOR UPDATE
     "#;
 
-    let rows: Vec<(Uuid, Uuid, String, String, i64)> = sqlx::query_as(q)
+    let row: Option<Row> = sqlx::query_as(q)
         .bind(business_id)
-        .fetch_all(pool)
+        .bind(id)
+        .fetch_optional(executor)
         .await?;
 
-    Ok(rows
-        .into_iter()
-        .map(|(id, business_id, name, currency, balance)| Account {
-            id,
-            business_id,
-            name,
-            currency,
-            balance,
-        })
-        .collect())
+    Ok(row.map(|r| (FxQuote::from(r.quote), r.expired)))
 }
 
-pub async fn get_account(
-    pool: &PgPool,
+pub async fn mark_fx_quote_used<'e>(
+    executor: impl PgExecutor<'e>,
+    id: Uuid,
+    transaction_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let q = r#"
+        UPDATE fx_quotes
+        SET used_at = now(), transaction_id = $2
+        WHERE id = $1
+    "#;
+
+    sqlx::query(q)
+        .bind(id)
+        .bind(transaction_id)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct HoldRow {
+    id: Uuid,
     business_id: Uuid,
     account_id: Uuid,
-) -> Result<Option<Account>, sqlx::Error> {
+    amount: i64,
+    currency: String,
+    status: String,
+    captured_amount: Option<i64>,
+    transaction_id: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+    released_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+fn hold_from_row(row: HoldRow) -> Option<Hold> {
+    Some(Hold {
+        id: row.id,
+        business_id: row.business_id,
+        account_id: row.account_id,
+        amount: row.amount,
+        currency: row.currency,
+        status: HoldStatus::from_db_str(&row.status)?,
+        captured_amount: row.captured_amount,
+        transaction_id: row.transaction_id,
+        expires_at: row.expires_at,
+        released_at: row.released_at,
+        created_at: row.created_at,
+    })
+}
+
+pub async fn insert_hold<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    account_id: Uuid,
+    amount: i64,
+    currency: &str,
+    expires_in_secs: i64,
+) -> Result<Option<Hold>, sqlx::Error> {
     let q = r#"
-        SELECT id, business_id, name, currency, balance
-        FROM accounts
-        WHERE id = $1 AND business_id = $2
-        LIMIT 1
+        INSERT INTO holds (business_id, account_id, amount, currency, expires_at)
+        VALUES ($1, $2, $3, $4, now() + make_interval(secs => $5))
+        RETURNING
+            id, business_id, account_id, amount, currency, status::text AS status,
+            captured_amount, transaction_id, expires_at, released_at, created_at
     "#;
 
-    let row: Option<(Uuid, Uuid, String, String, i64)> = sqlx::query_as(q)
+    let row: HoldRow = sqlx::query_as(q)
+        .bind(business_id)
         .bind(account_id)
+        .bind(amount)
+        .bind(currency.trim())
+        .bind(expires_in_secs as f64)
+        .fetch_one(executor)
+        .await?;
+
+    Ok(hold_from_row(row))
+}
+
+pub async fn get_hold(
+    pool: &PgPool,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<Hold>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, account_id, amount, currency, status::text AS status,
+            captured_amount, transaction_id, expires_at, released_at, created_at
+        FROM holds
+        WHERE business_id = $1 AND id = $2
+    "#;
+
+    let row: Option<HoldRow> = sqlx::query_as(q)
         .bind(business_id)
+        .bind(id)
         .fetch_optional(pool)
         .await?;
 
-    Ok(row.map(|(id, business_id, name, currency, balance)| Account {
-        id,
-        business_id,
-        name,
-        currency,
-        balance,
-    }))
+    Ok(row.and_then(hold_from_row))
 }
 
-pub async fn list_transactions(
+pub async fn list_account_holds(
     pool: &PgPool,
     business_id: Uuid,
-) -> Result<Vec<Transaction>, sqlx::Error> {
+    account_id: Uuid,
+    status: Option<HoldStatus>,
+) -> Result<Vec<Hold>, sqlx::Error> {
     let q = r#"
-        SELECT id, type::text, source_account_id, dest_account_id, amount, created_at
-        FROM transactions
-        WHERE business_id = $1
-        ORDER BY created_at DESC
-        LIMIT 100
+        SELECT
+            id, business_id, account_id, amount, currency, status::text AS status,
+            captured_amount, transaction_id, expires_at, released_at, created_at
+        FROM holds
+        WHERE business_id = $1 AND account_id = $2
+            AND ($3::hold_status IS NULL OR status = $3::hold_status)
+        ORDER BY created_at DESC, id DESC
     "#;
 
-    let rows: Vec<(Uuid, String, Option<Uuid>, Option<Uuid>, i64, DateTime<Utc>)> = sqlx::query_as(q)
+    let rows: Vec<HoldRow> = sqlx::query_as(q)
         .bind(business_id)
+        .bind(account_id)
+        .bind(status.map(HoldStatus::as_db_str))
         .fetch_all(pool)
         .await?;
 
-    let mut out = Vec::with_capacity(rows.len());
-    for (id, tx_type, source_account_id, dest_account_id, amount, created_at) in rows {
-        let tx_type = match tx_type.as_str() {
-            "credit" => TransactionType::Credit,
-            "debit" => TransactionType::Debit,
-            "transfer" => TransactionType::Transfer,
-            _ => continue,
-        };
+    Ok(rows.into_iter().filter_map(hold_from_row).collect())
+}
 
-        out.push(Transaction {
-            id,
-            business_id,
-            tx_type,
-            source_account_id,
-            dest_account_id,
-            amount,
-            created_at,
-        });
+// Locks the hold for the rest of the DB transaction; capture, void and expiry of one hold
+// serialize on it. Also returns whether it has expired, by the DB clock.
+pub async fn lock_hold_for_update<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<(Hold, bool)>, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        #[sqlx(flatten)]
+        hold: HoldRow,
+        expired: bool,
     }
 
-    Ok(out)
+    let q = r#"
+        SELECT
+            id, business_id, account_id, amount, currency, status::text AS status,
+            captured_amount, transaction_id, expires_at, released_at, created_at,
+            expires_at <= now() AS expired
+        FROM holds
+        WHERE business_id = $1 AND id = $2
+        FOR UPDATE
+    "#;
+
+    let row: Option<Row> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(|r| Some((hold_from_row(r.hold)?, r.expired))))
 }
 
-pub async fn get_transaction(
+// Moves an active hold to its final status; `capture` is (captured_amount, transaction_id).
+pub async fn release_hold<'e>(
+    executor: impl PgExecutor<'e>,
+    id: Uuid,
+    status: HoldStatus,
+    capture: Option<(i64, Uuid)>,
+) -> Result<Option<Hold>, sqlx::Error> {
+    let q = r#"
+        UPDATE holds
+        SET status = $2::hold_status, captured_amount = $3, transaction_id = $4,
+            released_at = now()
+        WHERE id = $1 AND status = 'active'
+        RETURNING
+            id, business_id, account_id, amount, currency, status::text AS status,
+            captured_amount, transaction_id, expires_at, released_at, created_at
+    "#;
+
+    let row: Option<HoldRow> = sqlx::query_as(q)
+        .bind(id)
+        .bind(status.as_db_str())
+        .bind(capture.map(|(amount, _)| amount))
+        .bind(capture.map(|(_, transaction_id)| transaction_id))
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(hold_from_row))
+}
+
+// `delta` is added to the account's cached sum of active holds.
+pub async fn adjust_held_amount<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+    delta: i64,
+) -> Result<(), sqlx::Error> {
+    let q = r#"
+        UPDATE accounts
+        SET held_amount = held_amount + $2
+        WHERE id = $1
+    "#;
+
+    sqlx::query(q)
+        .bind(account_id)
+        .bind(delta)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+// (business_id, hold_id) of active holds past their expiry, oldest first.
+pub async fn list_expired_holds(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+    let q = r#"
+        SELECT business_id, id
+        FROM holds
+        WHERE status = 'active' AND expires_at <= now()
+        ORDER BY expires_at ASC
+        LIMIT $1
+    "#;
+
+    sqlx::query_as(q).bind(limit).fetch_all(pool).await
+}
+
+#[derive(sqlx::FromRow)]
+struct ScheduledTransactionRow {
+    id: Uuid,
+    business_id: Uuid,
+    tx_type: String,
+    source_account_id: Option<Uuid>,
+    dest_account_id: Option<Uuid>,
+    amount: i64,
+    frequency: Option<String>,
+    start_at: DateTime<Utc>,
+    end_at: Option<DateTime<Utc>>,
+    status: String,
+    run_count: i32,
+    next_run_at: Option<DateTime<Utc>>,
+    last_run_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+fn scheduled_transaction_from_row(row: ScheduledTransactionRow) -> Option<ScheduledTransaction> {
+    let frequency = match row.frequency {
+        Some(f) => Some(ScheduleFrequency::from_db_str(&f)?),
+        None => None,
+    };
+
+    Some(ScheduledTransaction {
+        id: row.id,
+        business_id: row.business_id,
+        tx_type: TransactionType::from_db_str(&row.tx_type)?,
+        source_account_id: row.source_account_id,
+        dest_account_id: row.dest_account_id,
+        amount: row.amount,
+        frequency,
+        start_at: row.start_at,
+        end_at: row.end_at,
+        status: ScheduleStatus::from_db_str(&row.status)?,
+        run_count: row.run_count,
+        next_run_at: row.next_run_at,
+        last_run_at: row.last_run_at,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
+
+pub async fn insert_scheduled_transaction(
+    pool: &PgPool,
+    business_id: Uuid,
+    req: &CreateScheduledTransactionRequest,
+) -> Result<Option<ScheduledTransaction>, sqlx::Error> {
+    let q = r#"
+        INSERT INTO scheduled_transactions (
+            business_id, type, source_account_id, dest_account_id, amount,
+            frequency, start_at, end_at, next_run_at
+        )
+        VALUES ($1, $2::transaction_type, $3, $4, $5, $6::schedule_frequency, $7, $8, $7)
+        RETURNING
+            id, business_id, type::text AS tx_type, source_account_id, dest_account_id, amount,
+            frequency::text AS frequency, start_at, end_at, status::text AS status, run_count,
+            next_run_at, last_run_at, created_at, updated_at
+    "#;
+
+    let row: ScheduledTransactionRow = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(req.tx_type.as_db_str())
+        .bind(req.source_account_id)
+        .bind(req.dest_account_id)
+        .bind(req.amount)
+        .bind(req.frequency.map(ScheduleFrequency::as_db_str))
+        .bind(req.start_at)
+        .bind(req.end_at)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(scheduled_transaction_from_row(row))
+}
+
+pub async fn get_scheduled_transaction(
     pool: &PgPool,
     business_id: Uuid,
     id: Uuid,
-) -> Result<Option<Transaction>, sqlx::Error> {
+) -> Result<Option<ScheduledTransaction>, sqlx::Error> {
     let q = r#"
-        SELECT id, type::text, source_account_id, dest_account_id, amount, created_at
-        FROM transactions
+        SELECT
+            id, business_id, type::text AS tx_type, source_account_id, dest_account_id, amount,
+            frequency::text AS frequency, start_at, end_at, status::text AS status, run_count,
+            next_run_at, last_run_at, created_at, updated_at
+        FROM scheduled_transactions
         WHERE business_id = $1 AND id = $2
-        LIMIT 1
     "#;
 
-    let row: Option<(Uuid, String, Option<Uuid>, Option<Uuid>, i64, DateTime<Utc>)> = sqlx::query_as(q)
+    let row: Option<ScheduledTransactionRow> = sqlx::query_as(q)
         .bind(business_id)
         .bind(id)
         .fetch_optional(pool)
         .await?;
 
-    let Some((id, tx_type, source_account_id, dest_account_id, amount, created_at)) = row else {
-        return Ok(None);
-    };
+    Ok(row.and_then(scheduled_transaction_from_row))
+}
+
+pub async fn list_scheduled_transactions(
+    pool: &PgPool,
+    business_id: Uuid,
+    status: Option<ScheduleStatus>,
+) -> Result<Vec<ScheduledTransaction>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, business_id, type::text AS tx_type, source_account_id, dest_account_id, amount,
+            frequency::text AS frequency, start_at, end_at, status::text AS status, run_count,
+            next_run_at, last_run_at, created_at, updated_at
+        FROM scheduled_transactions
+        WHERE business_id = $1
+            AND ($2::schedule_status IS NULL OR status = $2::schedule_status)
+        ORDER BY created_at DESC, id DESC
+    "#;
+
+    let rows: Vec<ScheduledTransactionRow> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(status.map(ScheduleStatus::as_db_str))
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().filter_map(scheduled_transaction_from_row).collect())
+}
+
+// Locks the schedule for the rest of the DB transaction; a run and a cancel of one schedule
+// serialize on it. Also returns whether its next run is due, by the DB clock.
+pub async fn lock_scheduled_transaction_for_update<'e>(
+    executor: impl PgExecutor<'e>,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<Option<(ScheduledTransaction, bool)>, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        #[sqlx(flatten)]
+        schedule: ScheduledTransactionRow,
+        due: bool,
+    }
+
+    let q = r#"
+        SELECT
+            id, business_id, type::text AS tx_type, source_account_id, dest_account_id, amount,
+            frequency::text AS frequency, start_at, end_at, status::text AS status, run_count,
+            next_run_at, last_run_at, created_at, updated_at,
+            COALESCE(next_run_at <= now(), false) AS due
+        FROM scheduled_transactions
+        WHERE business_id = $1 AND id = $2
+        FOR UPDATE
+    "#;
+
+    let row: Option<Row> = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(|r| Some((scheduled_transaction_from_row(r.schedule)?, r.due))))
+}
+
+// Records that the current occurrence ran; the schedule completes when `next_run_at` is None.
+pub async fn advance_scheduled_transaction<'e>(
+    executor: impl PgExecutor<'e>,
+    id: Uuid,
+    next_run_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    let q = r#"
+        UPDATE scheduled_transactions
+        SET run_count = run_count + 1,
+            last_run_at = now(),
+            next_run_at = $2,
+            status = CASE WHEN $2::timestamptz IS NULL THEN 'completed' ELSE status END,
+            updated_at = now()
+        WHERE id = $1
+    "#;
+
+    sqlx::query(q)
+        .bind(id)
+        .bind(next_run_at)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn cancel_scheduled_transaction<'e>(
+    executor: impl PgExecutor<'e>,
+    id: Uuid,
+) -> Result<Option<ScheduledTransaction>, sqlx::Error> {
+    let q = r#"
+        UPDATE scheduled_transactions
+        SET status = 'cancelled', next_run_at = NULL, updated_at = now()
+        WHERE id = $1 AND status = 'active'
+        RETURNING
+            id, business_id, type::text AS tx_type, source_account_id, dest_account_id, amount,
+            frequency::text AS frequency, start_at, end_at, status::text AS status, run_count,
+            next_run_at, last_run_at, created_at, updated_at
+    "#;
+
+    let row: Option<ScheduledTransactionRow> = sqlx::query_as(q)
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(row.and_then(scheduled_transaction_from_row))
+}
+
+// (business_id, schedule_id) of active schedules whose next run is due, oldest first.
+pub async fn list_due_scheduled_transactions(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+    let q = r#"
+        SELECT business_id, id
+        FROM scheduled_transactions
+        WHERE status = 'active' AND next_run_at <= now()
+        ORDER BY next_run_at ASC
+        LIMIT $1
+    "#;
+
+    sqlx::query_as(q).bind(limit).fetch_all(pool).await
+}
+
+#[derive(sqlx::FromRow)]
+struct ScheduledTransactionRunRow {
+    id: Uuid,
+    schedule_id: Uuid,
+    occurrence: i32,
+    scheduled_for: DateTime<Utc>,
+    status: String,
+    transaction_id: Option<Uuid>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+}
 
-    let tx_type = match tx_type.as_str() {
-        "credit" => TransactionType::Credit,
-        "debit" => TransactionType::Debit,
-        "transfer" => TransactionType::Transfer,
-        _ => return Ok(None),
+fn scheduled_run_from_row(row: ScheduledTransactionRunRow) -> Option<ScheduledTransactionRun> {
+    let error = match row.error {
+        Some(e) => Some(serde_json::from_str(&e).ok()?),
+        None => None,
     };
 
-    Ok(Some(Transaction {
-        id,
-        business_id,
-        tx_type,
-        source_account_id,
-        dest_account_id,
-        amount,
-        created_at,
-    }))
+    Some(ScheduledTransactionRun {
+        id: row.id,
+        schedule_id: row.schedule_id,
+        occurrence: row.occurrence,
+        scheduled_for: row.scheduled_for,
+        status: ScheduledRunStatus::from_db_str(&row.status)?,
+        transaction_id: row.transaction_id,
+        error,
+        created_at: row.created_at,
+    })
+}
+
+// `error_json` is set for failed runs, `transaction_id` for successful ones.
+pub async fn insert_scheduled_transaction_run<'e>(
+    executor: impl PgExecutor<'e>,
+    schedule: &ScheduledTransaction,
+    scheduled_for: DateTime<Utc>,
+    status: ScheduledRunStatus,
+    transaction_id: Option<Uuid>,
+    error_json: Option<&str>,
+) -> Result<Option<ScheduledTransactionRun>, sqlx::Error> {
+    let q = r#"
+        INSERT INTO scheduled_transaction_runs (
+            schedule_id, occurrence, scheduled_for, status, transaction_id, error
+        )
+        VALUES ($1, $2, $3, $4::scheduled_run_status, $5, $6::jsonb)
+        RETURNING
+            id, schedule_id, occurrence, scheduled_for, status::text AS status, transaction_id,
+            error::text AS error, created_at
+    "#;
+
+    let row: ScheduledTransactionRunRow = sqlx::query_as(q)
+        .bind(schedule.id)
+        .bind(schedule.run_count)
+        .bind(scheduled_for)
+        .bind(status.as_db_str())
+        .bind(transaction_id)
+        .bind(error_json)
+        .fetch_one(executor)
+        .await?;
+
+    Ok(scheduled_run_from_row(row))
+}
+
+pub async fn list_scheduled_transaction_runs(
+    pool: &PgPool,
+    schedule_id: Uuid,
+) -> Result<Vec<ScheduledTransactionRun>, sqlx::Error> {
+    let q = r#"
+        SELECT
+            id, schedule_id, occurrence, scheduled_for, status::text AS status, transaction_id,
+            error::text AS error, created_at
+        FROM scheduled_transaction_runs
+        WHERE schedule_id = $1
+        ORDER BY occurrence DESC
+    "#;
+
+    let rows: Vec<ScheduledTransactionRunRow> = sqlx::query_as(q)
+        .bind(schedule_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().filter_map(scheduled_run_from_row).collect())
 }
 
 pub async fn create_webhook_endpoint(
     pool: &PgPool,
     business_id: Uuid,
     url: &str,
+    secret: &str,
 ) -> Result<WebhookEndpoint, sqlx::Error> {
     let q = r#"
         INSERT INTO webhook_endpoints (business_id, url, secret, active)
-        VALUES ($1, $2, '', true)
+        VALUES ($1, $2, $3, true)
         RETURNING id, business_id, url, active, created_at
     "#;
 
     let row: (Uuid, Uuid, String, bool, DateTime<Utc>) = sqlx::query_as(q)
         .bind(business_id)
         .bind(url)
+        .bind(secret)
         .fetch_one(pool)
         .await?;
 
@@ -258,10 +1866,13 @@ pub async fn deactivate_webhook_endpoint(
     Ok(result.rows_affected() > 0)
 }
 
-pub async fn enqueue_webhook_events_for_transaction(
-    pool: &PgPool,
+// Takes any executor so callers can enqueue inside the DB transaction that
+// created the money movement (transactional outbox). `transaction_id` is None for events
+// that are not about a transaction.
+pub async fn enqueue_webhook_events<'e>(
+    executor: impl PgExecutor<'e>,
     business_id: Uuid,
-    transaction_id: Uuid,
+    transaction_id: Option<Uuid>,
     payload_json: &str,
 ) -> Result<u64, sqlx::Error> {
     let q = r#"
@@ -275,7 +1886,7 @@ pub async fn enqueue_webhook_events_for_transaction(
         .bind(business_id)
         .bind(transaction_id)
         .bind(payload_json)
-        .execute(pool)
+        .execute(executor)
         .await?;
 
     Ok(result.rows_affected())
@@ -285,6 +1896,7 @@ pub async fn enqueue_webhook_events_for_transaction(
 pub struct DueWebhookEvent {
     pub event_id: Uuid,
     pub url: String,
+    pub secret: String,
     pub payload_json: String,
     pub attempts: i32,
 }
@@ -297,6 +1909,7 @@ pub async fn fetch_due_webhook_events(
         SELECT
             e.id AS event_id,
             w.url,
+            w.secret,
             e.payload::text AS payload_json,
             e.attempts
         FROM webhook_events e
@@ -309,16 +1922,17 @@ pub async fn fetch_due_webhook_events(
         LIMIT $1
     "#;
 
-    let rows: Vec<(Uuid, String, String, i32)> = sqlx::query_as(q)
+    let rows: Vec<(Uuid, String, String, String, i32)> = sqlx::query_as(q)
         .bind(limit)
         .fetch_all(pool)
         .await?;
 
     Ok(rows
         .into_iter()
-        .map(|(event_id, url, payload_json, attempts)| DueWebhookEvent {
+        .map(|(event_id, url, secret, payload_json, attempts)| DueWebhookEvent {
             event_id,
             url,
+            secret,
             payload_json,
             attempts,
         })
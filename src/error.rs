@@ -1,25 +1,297 @@
 use axum::{Json, http::StatusCode, response::IntoResponse};
+use serde::Serialize;
 use serde_json::json;
 
+use crate::{
+    middleware::request_id::current_request_id,
+    models::account::AccountLimit,
+    services::{
+        account_service::AccountError, api_key_service::ApiKeyError, fx_service::FxError,
+        hold_service::HoldError, scheduled_transaction_service::ScheduleError,
+        transaction_service::TransactionError, webhook_service::WebhookError,
+    },
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+// Every variant has a stable `code` that clients can branch on; `error` stays human-readable.
 #[derive(Debug)]
 pub enum ApiError {
     Unauthorized, // 401
+    Forbidden(&'static str), // 403
     NotFound, // 404
+    AccountNotFound, // 404
+    AccountFrozen, // 409
+    AccountClosed, // 409
+    ValidationFailed(Vec<FieldError>), // 400
+    UnsupportedMediaType, // 415
+    InsufficientFunds, // 400
+    CurrencyMismatch, // 400
+    Conflict(&'static str), // 409
+    IdempotencyKeyReused, // 422
+    QuoteExpired, // 400
+    LimitExceeded(AccountLimit), // 400
     InternalError, // 500
 }
 
+impl ApiError {
+    pub fn validation(field: &str, message: &str) -> Self {
+        ApiError::ValidationFailed(vec![FieldError::new(field, message)])
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound => "not_found",
+            ApiError::AccountNotFound => "account_not_found",
+            ApiError::AccountFrozen => "account_frozen",
+            ApiError::AccountClosed => "account_closed",
+            ApiError::ValidationFailed(_) => "validation_failed",
+            ApiError::UnsupportedMediaType => "unsupported_media_type",
+            ApiError::InsufficientFunds => "insufficient_funds",
+            ApiError::CurrencyMismatch => "currency_mismatch",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::IdempotencyKeyReused => "idempotency_key_reused",
+            ApiError::QuoteExpired => "quote_expired",
+            ApiError::LimitExceeded(_) => "limit_exceeded",
+            ApiError::InternalError => "internal_error",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound | ApiError::AccountNotFound => StatusCode::NOT_FOUND,
+            ApiError::ValidationFailed(_)
+            | ApiError::InsufficientFunds
+            | ApiError::CurrencyMismatch
+            | ApiError::QuoteExpired
+            | ApiError::LimitExceeded(_) => StatusCode::BAD_REQUEST,
+            ApiError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Conflict(_) | ApiError::AccountFrozen | ApiError::AccountClosed => {
+                StatusCode::CONFLICT
+            }
+            ApiError::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            ApiError::Unauthorized => "Unauthorized",
+            ApiError::Forbidden(msg) | ApiError::Conflict(msg) => msg,
+            ApiError::NotFound => "Data Not found",
+            ApiError::AccountNotFound => "account not found",
+            ApiError::AccountFrozen => "account is frozen and cannot send money",
+            ApiError::AccountClosed => "account is closed",
+            // A single failing field keeps its full sentence as the top-level message.
+            ApiError::ValidationFailed(details) => match details.as_slice() {
+                [only] => &only.message,
+                _ => "request validation failed",
+            },
+            ApiError::UnsupportedMediaType => "expected Content-Type: application/json",
+            ApiError::InsufficientFunds => "insufficient funds",
+            ApiError::CurrencyMismatch => {
+                "accounts have different currencies and no FX rate is available"
+            }
+            ApiError::IdempotencyKeyReused => {
+                "Idempotency-Key was already used with a different request body"
+            }
+            ApiError::QuoteExpired => "FX quote has expired; request a new quote",
+            ApiError::LimitExceeded(limit) => match limit {
+                AccountLimit::MaxTransactionAmount => {
+                    "amount exceeds the account's max_transaction_amount"
+                }
+                AccountLimit::MaxDailyOutflowCount => {
+                    "account has reached its max_daily_outflow_count for today"
+                }
+                AccountLimit::MaxDailyOutflowAmount => {
+                    "amount exceeds what is left of the account's max_daily_outflow_amount today"
+                }
+                AccountLimit::MinBalance => "amount would take the account below its min_balance",
+            },
+            ApiError::InternalError => "Internal Server error",
+        }
+    }
+}
+
+impl ApiError {
+    /// `code`, `error`, `details` (validation failures) and `limit` (`limit_exceeded`),
+    /// without the request id; also used for per-item errors in batch responses.
+    pub fn body(&self) -> serde_json::Value {
+        let mut body = json!({
+            "code": self.code(),
+            "error": self.message(),
+        });
+        match self {
+            ApiError::ValidationFailed(details) => body["details"] = json!(details),
+            ApiError::LimitExceeded(limit) => body["limit"] = json!(limit.as_str()),
+            _ => {}
+        }
+        body
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
-        let (status, error_message) = match self {
-            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
-            ApiError::NotFound => (StatusCode::NOT_FOUND, "Data Not found".to_string()),
-            ApiError::InternalError => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal Sevrer error".to_string(),
-            ),
-        };
+        let mut body = self.body();
+        body["request_id"] = json!(current_request_id());
 
-        let body = Json(json!({ "error": error_message }));
-        (status, body).into_response()
+        (self.status(), Json(body)).into_response()
     }
-}
\ No newline at end of file
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(_: sqlx::Error) -> Self {
+        ApiError::InternalError
+    }
+}
+
+impl From<TransactionError> for ApiError {
+    fn from(err: TransactionError) -> Self {
+        match err {
+            TransactionError::Validation { field, message } => ApiError::validation(field, message),
+            TransactionError::AccountNotFound => ApiError::AccountNotFound,
+            TransactionError::AccountFrozen => ApiError::AccountFrozen,
+            TransactionError::AccountClosed => ApiError::AccountClosed,
+            TransactionError::InsufficientFunds => ApiError::InsufficientFunds,
+            TransactionError::CurrencyMismatch => ApiError::CurrencyMismatch,
+            TransactionError::IdempotencyKeyReused => ApiError::IdempotencyKeyReused,
+            TransactionError::QuoteNotFound => ApiError::NotFound,
+            TransactionError::QuoteAlreadyUsed => ApiError::Conflict("quote has already been used"),
+            TransactionError::QuoteExpired => ApiError::QuoteExpired,
+            TransactionError::TransactionNotFound => ApiError::NotFound,
+            TransactionError::AlreadyReversed => {
+                ApiError::Conflict("transaction has already been fully reversed")
+            }
+            TransactionError::LimitExceeded(limit) => ApiError::LimitExceeded(limit),
+            TransactionError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for TransactionError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<WebhookError> for ApiError {
+    fn from(err: WebhookError) -> Self {
+        match err {
+            WebhookError::Validation { field, message } => ApiError::validation(field, message),
+            WebhookError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<ApiKeyError> for ApiError {
+    fn from(err: ApiKeyError) -> Self {
+        match err {
+            ApiKeyError::Validation { field, message } => ApiError::validation(field, message),
+            ApiKeyError::Forbidden(msg) => ApiError::Forbidden(msg),
+            ApiKeyError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for ApiKeyError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<FxError> for ApiError {
+    fn from(err: FxError) -> Self {
+        match err {
+            FxError::Validation { field, message } => ApiError::validation(field, message),
+            FxError::AccountNotFound => ApiError::AccountNotFound,
+            FxError::CurrencyMismatch => ApiError::CurrencyMismatch,
+            FxError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for FxError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<HoldError> for ApiError {
+    fn from(err: HoldError) -> Self {
+        match err {
+            HoldError::Validation { field, message } => ApiError::validation(field, message),
+            HoldError::AccountNotFound => ApiError::AccountNotFound,
+            HoldError::AccountFrozen => ApiError::AccountFrozen,
+            HoldError::AccountClosed => ApiError::AccountClosed,
+            HoldError::HoldNotFound => ApiError::NotFound,
+            HoldError::InsufficientFunds => ApiError::InsufficientFunds,
+            HoldError::CurrencyMismatch => ApiError::CurrencyMismatch,
+            HoldError::LimitExceeded(limit) => ApiError::LimitExceeded(limit),
+            HoldError::NotActive(msg) => ApiError::Conflict(msg),
+            HoldError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for HoldError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<AccountError> for ApiError {
+    fn from(err: AccountError) -> Self {
+        match err {
+            AccountError::AccountNotFound => ApiError::AccountNotFound,
+            AccountError::InvalidStatus(msg) => ApiError::Conflict(msg),
+            AccountError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for AccountError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+impl From<ScheduleError> for ApiError {
+    fn from(err: ScheduleError) -> Self {
+        match err {
+            ScheduleError::Validation { field, message } => ApiError::validation(field, message),
+            ScheduleError::AccountNotFound => ApiError::AccountNotFound,
+            ScheduleError::ScheduleNotFound => ApiError::NotFound,
+            ScheduleError::NotActive(msg) => ApiError::Conflict(msg),
+            ScheduleError::Internal => ApiError::InternalError,
+        }
+    }
+}
+
+impl IntoResponse for ScheduleError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
@@ -0,0 +1,180 @@
+use axum::{
+    body::Bytes,
+    extract::{
+        FromRequest, FromRequestParts, OptionalFromRequest, Request,
+        path::ErrorKind,
+        rejection::PathRejection,
+    },
+    http::{header::CONTENT_TYPE, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+use crate::error::{ApiError, FieldError};
+
+/// Drop-in replacement for `axum::Json` whose rejections use the `ApiError` envelope.
+///
+/// Deserialization failures are reported as `validation_failed` with the path of the
+/// offending field, e.g. `{"field":"dest_acount_id","message":"unknown field ..."}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(&req) {
+            return Err(ApiError::UnsupportedMediaType);
+        }
+
+        let bytes = read_body(req, state).await?;
+        parse_body(&bytes).map(Json)
+    }
+}
+
+/// `Option<Json<T>>` for endpoints whose body is optional: an empty body (with or without a
+/// `Content-Type`) is `None`, anything else is handled like `Json<T>`.
+impl<T, S> OptionalFromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let json_content_type = has_json_content_type(&req);
+
+        let bytes = read_body(req, state).await?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        if !json_content_type {
+            return Err(ApiError::UnsupportedMediaType);
+        }
+
+        parse_body(&bytes).map(|value| Some(Json(value)))
+    }
+}
+
+async fn read_body<S: Send + Sync>(req: Request, state: &S) -> Result<Bytes, ApiError> {
+    Bytes::from_request(req, state)
+        .await
+        .map_err(|_| ApiError::validation("body", "failed to read request body"))
+}
+
+fn parse_body<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    let de = &mut serde_json::Deserializer::from_slice(bytes);
+    serde_path_to_error::deserialize(de).map_err(rejection_from_serde)
+}
+
+impl<T: serde::Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+fn has_json_content_type(req: &Request) -> bool {
+    let Some(content_type) = req.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+fn rejection_from_serde(err: serde_path_to_error::Error<serde_json::Error>) -> ApiError {
+    let path = err.path().to_string();
+    let inner = err.into_inner();
+
+    if inner.is_syntax() || inner.is_eof() {
+        return ApiError::validation("body", &format!("malformed JSON: {inner}"));
+    }
+
+    // serde_json appends " at line L column C"; the field path is more useful to clients.
+    let message = inner.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    };
+
+    field_error(path, message, "body")
+}
+
+// `root` names the whole input (`body`, `query`) for errors that are not about one field.
+fn field_error(path: String, message: String, root: &str) -> ApiError {
+    // Missing fields are reported against the enclosing object; point at the field itself.
+    let field = match (message.strip_prefix("missing field `"), path.as_str()) {
+        (Some(rest), ".") => rest.trim_end_matches('`').to_string(),
+        (Some(rest), _) => format!("{path}.{}", rest.trim_end_matches('`')),
+        (None, ".") => root.to_string(),
+        (None, _) => path,
+    };
+
+    ApiError::ValidationFailed(vec![FieldError::new(field, message)])
+}
+
+/// Drop-in replacement for `axum::extract::Query` whose rejections use the `ApiError`
+/// envelope, with the offending parameter as `field`, e.g. `{"field":"limit",...}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        let de = serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+        serde_path_to_error::deserialize(de).map(Query).map_err(|err| {
+            let path = err.path().to_string();
+            field_error(path, err.into_inner().to_string(), "query")
+        })
+    }
+}
+
+/// Drop-in replacement for `axum::extract::Path` whose rejections use the `ApiError`
+/// envelope, with the offending path parameter as `field`, e.g. `{"field":"id",...}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Path<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        axum::extract::Path::<T>::from_request_parts(parts, state)
+            .await
+            .map(|axum::extract::Path(value)| Path(value))
+            .map_err(rejection_from_path)
+    }
+}
+
+fn rejection_from_path(rejection: PathRejection) -> ApiError {
+    let PathRejection::FailedToDeserializePathParams(err) = rejection else {
+        // The route has no path parameters: a handler bug, not a client error.
+        return ApiError::InternalError;
+    };
+
+    let kind = err.into_kind();
+    let field = match &kind {
+        ErrorKind::ParseErrorAtKey { key, .. }
+        | ErrorKind::DeserializeError { key, .. }
+        | ErrorKind::InvalidUtf8InPathParam { key } => key.clone(),
+        ErrorKind::WrongNumberOfParameters { .. } | ErrorKind::UnsupportedType { .. } => {
+            return ApiError::InternalError;
+        }
+        _ => "path".to_string(),
+    };
+
+    ApiError::ValidationFailed(vec![FieldError::new(field, kind.to_string())])
+}
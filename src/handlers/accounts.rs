@@ -1,24 +1,33 @@
 use axum::{
-    Json,
-    extract::{Extension, Path, State},
+    extract::{Extension, State},
     http::StatusCode,
     response::IntoResponse,
 };
-use serde_json::json;
+use chrono::Utc;
 use uuid::Uuid;
 
 use crate::{
-    db,
+    currency, db,
     error::ApiError,
+    metadata,
+    extract::{Json, Path, Query},
     middleware::auth::BusinessContext,
-    models::account::{AccountResponse, CreateAccountRequest},
+    models::{
+        account::{
+            AccountFilter, AccountLimits, AccountResponse, AccountStatus, BalanceAtQuery,
+            BalanceAtResponse, CreateAccountRequest, DailyBalancesQuery, DailyBalancesResponse,
+            ListAccountsQuery, OverdraftLimitChangeResponse, SetOverdraftLimitRequest,
+            StatementEntryResponse, StatementQuery,
+        },
+        pagination::{Page, SeqCursor, normalize_limit},
+    },
+    services::account_service,
     state::AppState,
 };
 
 fn normalize_currency(input: &str) -> Option<String> {
     let c = input.trim().to_ascii_uppercase();
-    let ok = c.len() == 3 && c.chars().all(|ch| ch.is_ascii_alphabetic());
-    ok.then_some(c)
+    currency::find(&c).map(|found| found.code.to_string())
 }
 
 fn normalize_name(input: &str) -> Option<String> {
@@ -44,62 +53,330 @@ pub async fn create_account(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
     Json(payload): Json<CreateAccountRequest>,
-) -> impl IntoResponse {
-    let name = match normalize_name(&payload.name) {
-        Some(n) => n,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "name is required (1-128 chars)" })),
-            )
-                .into_response();
-        }
+) -> Result<impl IntoResponse, ApiError> {
+    // The new account would be outside the key's allowlist, i.e. invisible to it.
+    if ctx.allowed_account_ids.is_some() {
+        return Err(ApiError::Forbidden(
+            "account-restricted API keys cannot create accounts",
+        ));
+    }
+
+    let name = normalize_name(&payload.name)
+        .ok_or_else(|| ApiError::validation("name", "name is required (1-128 chars)"))?;
+
+    let currency = normalize_currency(&payload.currency).ok_or_else(|| {
+        ApiError::validation("currency", "currency must be an ISO 4217 code, e.g. USD")
+    })?;
+
+    let metadata = payload.metadata.unwrap_or_default();
+    metadata::validate(&metadata).map_err(|msg| ApiError::validation("metadata", msg))?;
+
+    let external_reference = payload.external_reference.as_deref();
+    if let Some(r) = external_reference {
+        metadata::validate_external_reference(r)
+            .map_err(|msg| ApiError::validation("external_reference", msg))?;
+    }
+
+    let created = db::create_account(
+        &state.pool,
+        ctx.business_id,
+        &name,
+        &currency,
+        &metadata,
+        external_reference,
+    )
+    .await;
+
+    match created {
+        Ok(Some(account)) => Ok((StatusCode::CREATED, Json(AccountResponse::from(account)))),
+        Ok(None) => Err(ApiError::InternalError),
+        Err(e) if is_unique_violation(&e) => Err(ApiError::Conflict(
+            "account name already exists for this business",
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// GET /api/accounts?external_reference=...&metadata={"key":"value"}
+pub async fn list_accounts(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Query(query): Query<ListAccountsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let metadata = match query.metadata.as_deref() {
+        None => None,
+        Some(raw) => Some(metadata::parse_filter(raw).ok_or_else(|| {
+            ApiError::validation("metadata", metadata::INVALID_FILTER_MESSAGE)
+        })?),
     };
 
-    let currency = match normalize_currency(&payload.currency) {
-        Some(c) => c,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "currency must be a 3-letter code, e.g. USD" })),
-            )
-                .into_response();
-        }
+    let filter = AccountFilter {
+        external_reference: query.external_reference,
+        metadata,
+        allowed_account_ids: ctx.allowed_account_ids.clone(),
     };
+    let accounts = db::list_accounts(&state.pool, ctx.business_id, &filter).await?;
+
+    let resp: Vec<AccountResponse> = accounts.into_iter().map(AccountResponse::from).collect();
+    Ok(Json(resp))
+}
 
-    match db::create_account(&state.pool, ctx.business_id, &name, &currency).await {
-        Ok(account) => (StatusCode::CREATED, Json(AccountResponse::from(account))).into_response(),
-        Err(e) if is_unique_violation(&e) => (
-            StatusCode::CONFLICT,
-            Json(json!({ "error": "account name already exists for this business" })),
-        )
-            .into_response(),
-        Err(_) => ApiError::InternalError.into_response(),
+pub async fn get_account(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
     }
+
+    let account = db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    Ok(Json(AccountResponse::from(account)))
 }
 
-pub async fn list_accounts(
+async fn change_status(
+    state: &AppState,
+    ctx: &BusinessContext,
+    account_id: Uuid,
+    to: AccountStatus,
+) -> Result<Json<AccountResponse>, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let account = account_service::change_account_status(
+        &state.pool,
+        ctx.business_id,
+        allowed,
+        account_id,
+        to,
+    )
+    .await?;
+
+    Ok(Json(AccountResponse::from(account)))
+}
+
+pub async fn freeze_account(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
-) -> impl IntoResponse {
-    match db::list_accounts(&state.pool, ctx.business_id).await {
-        Ok(accounts) => {
-            let resp: Vec<AccountResponse> =
-                accounts.into_iter().map(AccountResponse::from).collect();
-            Json(resp).into_response()
-        }
-        Err(_) => ApiError::InternalError.into_response(),
+    Path(account_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    change_status(&state, &ctx, account_id, AccountStatus::Frozen).await
+}
+
+pub async fn unfreeze_account(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    change_status(&state, &ctx, account_id, AccountStatus::Active).await
+}
+
+pub async fn close_account(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    change_status(&state, &ctx, account_id, AccountStatus::Closed).await
+}
+
+// PUT /api/accounts/{id}/overdraft-limit
+pub async fn set_overdraft_limit(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<SetOverdraftLimitRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if payload.overdraft_limit < 0 {
+        return Err(ApiError::validation("overdraft_limit", "overdraft_limit must be >= 0"));
     }
+
+    let account = account_service::set_overdraft_limit(
+        &state.pool,
+        ctx.business_id,
+        ctx.api_key_id,
+        ctx.allowed_account_ids.as_deref(),
+        account_id,
+        payload.overdraft_limit,
+    )
+    .await?;
+
+    Ok(Json(AccountResponse::from(account)))
 }
 
-pub async fn get_account(
+fn validate_limits(limits: &AccountLimits) -> Result<(), ApiError> {
+    if limits.max_transaction_amount.is_some_and(|v| v <= 0) {
+        return Err(ApiError::validation(
+            "max_transaction_amount",
+            "max_transaction_amount must be > 0",
+        ));
+    }
+    if limits.max_daily_outflow_count.is_some_and(|v| v <= 0) {
+        return Err(ApiError::validation(
+            "max_daily_outflow_count",
+            "max_daily_outflow_count must be > 0",
+        ));
+    }
+    if limits.max_daily_outflow_amount.is_some_and(|v| v <= 0) {
+        return Err(ApiError::validation(
+            "max_daily_outflow_amount",
+            "max_daily_outflow_amount must be > 0",
+        ));
+    }
+    if limits.min_balance.is_some_and(|v| v < 0) {
+        return Err(ApiError::validation("min_balance", "min_balance must be >= 0"));
+    }
+    Ok(())
+}
+
+// PUT /api/accounts/{id}/limits; omitted or null limits are removed.
+pub async fn set_account_limits(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
     Path(account_id): Path<Uuid>,
-) -> impl IntoResponse {
-    match db::get_account(&state.pool, ctx.business_id, account_id).await {
-        Ok(Some(account)) => Json(AccountResponse::from(account)).into_response(),
-        Ok(None) => ApiError::NotFound.into_response(),
-        Err(_) => ApiError::InternalError.into_response(),
+    Json(payload): Json<AccountLimits>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_limits(&payload)?;
+
+    let account = account_service::set_account_limits(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+        account_id,
+        &payload,
+    )
+    .await?;
+
+    Ok(Json(AccountResponse::from(account)))
+}
+
+pub async fn list_overdraft_limit_changes(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
+    }
+
+    db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    let changes = db::list_overdraft_limit_changes(&state.pool, ctx.business_id, account_id)
+        .await?;
+
+    let resp: Vec<OverdraftLimitChangeResponse> =
+        changes.into_iter().map(OverdraftLimitChangeResponse::from).collect();
+    Ok(Json(resp))
+}
+
+pub async fn list_account_transactions(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<StatementQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Some(limit) = normalize_limit(query.limit) else {
+        return Err(ApiError::validation("limit", "limit must be between 1 and 200"));
+    };
+
+    let cursor = match query.cursor.as_deref().map(SeqCursor::decode) {
+        None => None,
+        Some(Some(c)) => Some(c),
+        Some(None) => return Err(ApiError::validation("cursor", "invalid cursor")),
+    };
+
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
     }
+
+    db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    // Fetch one extra row to know whether another page exists.
+    let mut rows =
+        db::list_account_statement(&state.pool, ctx.business_id, account_id, cursor, limit + 1)
+            .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|e| SeqCursor(e.seq).encode())
+    } else {
+        None
+    };
+
+    let data: Vec<StatementEntryResponse> =
+        rows.into_iter().map(StatementEntryResponse::from).collect();
+    Ok(Json(Page { data, next_cursor }))
+}
+
+// Longest range GET /api/accounts/{id}/balances returns in one call.
+const MAX_BALANCE_RANGE_DAYS: i64 = 366;
+
+// GET /api/accounts/{id}/balance?at=2026-01-01T00:00:00Z
+pub async fn get_account_balance_at(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<BalanceAtQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let now = Utc::now();
+    let at = query.at.unwrap_or(now);
+    if at > now {
+        return Err(ApiError::validation("at", "at must not be in the future"));
+    }
+
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
+    }
+
+    let account = db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    let balance = db::account_balance_at(&state.pool, account_id, at).await?;
+
+    let currency = account.currency.trim().to_string();
+    Ok(Json(BalanceAtResponse {
+        account_id,
+        balance_decimal: currency::format_amount(balance, &currency),
+        currency,
+        at,
+        balance,
+    }))
+}
+
+// GET /api/accounts/{id}/balances?from=2026-01-01&to=2026-01-31
+pub async fn list_account_daily_balances(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<DailyBalancesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (Some(from), Some(to)) = (query.from, query.to) else {
+        return Err(ApiError::validation("from", "from and to are required, e.g. 2026-01-31"));
+    };
+    if from > to {
+        return Err(ApiError::validation("to", "to must not be before from"));
+    }
+    if (to - from).num_days() >= MAX_BALANCE_RANGE_DAYS {
+        return Err(ApiError::validation("to", "the range must be at most 366 days"));
+    }
+    if to > Utc::now().date_naive() {
+        return Err(ApiError::validation("to", "to must not be after today (UTC)"));
+    }
+
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
+    }
+
+    let account = db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    let balances = account_service::end_of_day_balances(&state.pool, account_id, from, to).await?;
+
+    Ok(Json(DailyBalancesResponse::new(&account, balances)))
 }
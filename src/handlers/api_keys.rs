@@ -0,0 +1,127 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::ApiError,
+    extract::{Json, Path},
+    middleware::auth::BusinessContext,
+    models::api_key::{
+        ApiKeyResponse, CreateApiKeyRequest, RotateApiKeyRequest, RotateApiKeyResponse,
+    },
+    services::api_key_service::{
+        ensure_manageable, generate_api_key, validate_requested_accounts,
+        validate_requested_scopes, validate_rotation_overlap,
+    },
+    state::AppState,
+};
+
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let scopes = validate_requested_scopes(&ctx, &payload.scopes)?;
+    let account_ids = validate_requested_accounts(&ctx, payload.account_ids.as_deref())?;
+
+    if let Some(ids) = &account_ids {
+        let found = db::count_accounts_in_business(&state.pool, ctx.business_id, ids).await?;
+        if found != ids.len() as i64 {
+            return Err(ApiError::validation(
+                "account_ids",
+                "account_ids contains an unknown account",
+            ));
+        }
+    }
+
+    let generated = generate_api_key(&state.hmac_secret);
+
+    let key = db::create_api_key(
+        &state.pool,
+        ctx.business_id,
+        &generated.hash,
+        &generated.prefix,
+        &generated.hmac_secret_fingerprint,
+        &scopes,
+        account_ids.as_deref(),
+    )
+    .await?;
+
+    let mut resp = ApiKeyResponse::from(key);
+    resp.key = Some(generated.raw);
+    Ok((StatusCode::CREATED, Json(resp)))
+}
+
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rows = db::list_api_keys(&state.pool, ctx.business_id).await?;
+
+    // Keys broader than the caller are not shown at all.
+    let resp: Vec<ApiKeyResponse> = rows
+        .into_iter()
+        .filter(|key| ensure_manageable(&ctx, key).is_ok())
+        .map(ApiKeyResponse::from)
+        .collect();
+    Ok(Json(resp))
+}
+
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let key = db::get_api_key(&state.pool, ctx.business_id, id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    ensure_manageable(&ctx, &key)?;
+
+    if !db::revoke_api_key(&state.pool, ctx.business_id, id).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn rotate_api_key(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<RotateApiKeyRequest>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let overlap_seconds = validate_rotation_overlap(payload.overlap_seconds)?;
+
+    // Scopes and allowlists never change, so checking before the rotation is enough.
+    let key = db::get_api_key(&state.pool, ctx.business_id, id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    ensure_manageable(&ctx, &key)?;
+
+    let generated = generate_api_key(&state.hmac_secret);
+
+    let (new_key, old_key) = db::rotate_api_key(
+        &state.pool,
+        ctx.business_id,
+        id,
+        &generated.hash,
+        &generated.prefix,
+        &generated.hmac_secret_fingerprint,
+        overlap_seconds,
+    )
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    let mut new_key = ApiKeyResponse::from(new_key);
+    new_key.key = Some(generated.raw);
+    let resp = RotateApiKeyResponse {
+        new_key,
+        old_key: ApiKeyResponse::from(old_key),
+    };
+    Ok((StatusCode::CREATED, Json(resp)))
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::ApiError,
+    extract::{Json, Path},
+    middleware::auth::BusinessContext,
+    models::fx::{CreateFxQuoteRequest, FxQuoteResponse, FxRateResponse, SetFxRateRequest},
+    services::fx_service::{self, validate_currency_pair, validate_rate},
+    state::AppState,
+};
+
+pub async fn list_fx_rates(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rows = db::list_fx_rates(&state.pool, ctx.business_id).await?;
+
+    let resp: Vec<FxRateResponse> = rows.into_iter().map(FxRateResponse::from).collect();
+    Ok(Json(resp))
+}
+
+// PUT /api/fx/rates/{base}/{quote}: creates or replaces the rate for one direction.
+pub async fn set_fx_rate(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path((base, quote)): Path<(String, String)>,
+    Json(payload): Json<SetFxRateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (base, quote) = validate_currency_pair(&base, &quote)?;
+    let rate = validate_rate(&payload.rate)?;
+
+    let row =
+        db::upsert_fx_rate(&state.pool, ctx.business_id, &base, &quote, &rate.to_string()).await?;
+
+    Ok(Json(FxRateResponse::from(row)))
+}
+
+pub async fn delete_fx_rate(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path((base, quote)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (base, quote) = validate_currency_pair(&base, &quote)?;
+
+    if !db::delete_fx_rate(&state.pool, ctx.business_id, &base, &quote).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_fx_quote(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Json(payload): Json<CreateFxQuoteRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let quote = fx_service::create_quote(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+        &payload,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(FxQuoteResponse::from(quote))))
+}
+
+pub async fn get_fx_quote(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let quote = db::get_fx_quote(&state.pool, ctx.business_id, id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    if !ctx.can_access_account(quote.source_account_id)
+        || !ctx.can_access_account(quote.dest_account_id)
+    {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(Json(FxQuoteResponse::from(quote)))
+}
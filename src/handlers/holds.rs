@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::ApiError,
+    extract::{Json, Path, Query},
+    middleware::auth::BusinessContext,
+    models::{
+        hold::{
+            CaptureHoldRequest, CaptureHoldResponse, CreateHoldRequest, HoldResponse,
+            ListHoldsQuery,
+        },
+        transaction::TransactionResponse,
+    },
+    services::hold_service,
+    state::AppState,
+};
+
+pub async fn create_hold(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Json(payload): Json<CreateHoldRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let hold = hold_service::place_hold(&state.pool, ctx.business_id, allowed, &payload).await?;
+
+    Ok((StatusCode::CREATED, Json(HoldResponse::from(hold))))
+}
+
+pub async fn get_hold(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let hold = db::get_hold(&state.pool, ctx.business_id, id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    if !ctx.can_access_account(hold.account_id) {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(Json(HoldResponse::from(hold)))
+}
+
+// GET /api/accounts/{id}/holds?status=active
+pub async fn list_account_holds(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<ListHoldsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !ctx.can_access_account(account_id) {
+        return Err(ApiError::AccountNotFound);
+    }
+    db::get_account(&state.pool, ctx.business_id, account_id)
+        .await?
+        .ok_or(ApiError::AccountNotFound)?;
+
+    let holds =
+        db::list_account_holds(&state.pool, ctx.business_id, account_id, query.status).await?;
+
+    let resp: Vec<HoldResponse> = holds.into_iter().map(HoldResponse::from).collect();
+    Ok(Json(resp))
+}
+
+pub async fn capture_hold(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CaptureHoldRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let (hold, transaction) =
+        hold_service::capture_hold(&state.pool, ctx.business_id, allowed, id, &payload).await?;
+
+    let resp = CaptureHoldResponse {
+        hold: HoldResponse::from(hold),
+        transaction: TransactionResponse::from(transaction),
+    };
+    Ok((StatusCode::CREATED, Json(resp)))
+}
+
+pub async fn void_hold(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let hold = hold_service::void_hold(&state.pool, ctx.business_id, allowed, id).await?;
+
+    Ok(Json(HoldResponse::from(hold)))
+}
@@ -0,0 +1,37 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    db,
+    error::ApiError,
+    middleware::auth::BusinessContext,
+    models::ledger::{AccountVerificationResponse, LedgerVerificationResponse},
+    state::AppState,
+};
+
+// Recomputes every account balance from postings and compares it to the cached accounts.balance.
+pub async fn verify_ledger(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rows = db::list_account_ledger_balances(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+    )
+    .await?;
+
+    let accounts: Vec<AccountVerificationResponse> = rows
+        .into_iter()
+        .map(AccountVerificationResponse::from)
+        .collect();
+    let consistent = accounts.iter().all(|a| a.consistent);
+
+    Ok(Json(LedgerVerificationResponse {
+        consistent,
+        accounts,
+    }))
+}
@@ -1,4 +1,9 @@
 pub mod health;
+pub mod holds;
 pub mod accounts;
+pub mod api_keys;
+pub mod fx;
+pub mod ledger;
+pub mod scheduled_transactions;
 pub mod transactions;
 pub mod webhooks;
\ No newline at end of file
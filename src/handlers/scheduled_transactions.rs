@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::ApiError,
+    extract::{Json, Path, Query},
+    middleware::auth::BusinessContext,
+    models::scheduled_transaction::{
+        CreateScheduledTransactionRequest, ListScheduledTransactionsQuery, ScheduledTransaction,
+        ScheduledTransactionResponse, ScheduledTransactionRunResponse,
+    },
+    services::scheduled_transaction_service,
+    state::AppState,
+};
+
+fn can_access(ctx: &BusinessContext, schedule: &ScheduledTransaction) -> bool {
+    schedule.account_ids().all(|id| ctx.can_access_account(id))
+}
+
+pub async fn create_scheduled_transaction(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Json(payload): Json<CreateScheduledTransactionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let schedule = scheduled_transaction_service::create_schedule(
+        &state.pool,
+        ctx.business_id,
+        allowed,
+        &payload,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(ScheduledTransactionResponse::from(schedule))))
+}
+
+// GET /api/scheduled-transactions?status=active
+pub async fn list_scheduled_transactions(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Query(query): Query<ListScheduledTransactionsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let schedules =
+        db::list_scheduled_transactions(&state.pool, ctx.business_id, query.status).await?;
+
+    let resp: Vec<ScheduledTransactionResponse> = schedules
+        .into_iter()
+        .filter(|s| can_access(&ctx, s))
+        .map(ScheduledTransactionResponse::from)
+        .collect();
+    Ok(Json(resp))
+}
+
+pub async fn get_scheduled_transaction(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let schedule = db::get_scheduled_transaction(&state.pool, ctx.business_id, id)
+        .await?
+        .filter(|s| can_access(&ctx, s))
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(ScheduledTransactionResponse::from(schedule)))
+}
+
+pub async fn list_scheduled_transaction_runs(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let schedule = db::get_scheduled_transaction(&state.pool, ctx.business_id, id)
+        .await?
+        .filter(|s| can_access(&ctx, s))
+        .ok_or(ApiError::NotFound)?;
+
+    let runs = db::list_scheduled_transaction_runs(&state.pool, schedule.id).await?;
+
+    let resp: Vec<ScheduledTransactionRunResponse> =
+        runs.into_iter().map(ScheduledTransactionRunResponse::from).collect();
+    Ok(Json(resp))
+}
+
+pub async fn cancel_scheduled_transaction(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let allowed = ctx.allowed_account_ids.as_deref();
+    let schedule =
+        scheduled_transaction_service::cancel_schedule(&state.pool, ctx.business_id, allowed, id)
+            .await?;
+
+    Ok(Json(ScheduledTransactionResponse::from(schedule)))
+}
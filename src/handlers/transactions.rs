@@ -1,73 +1,226 @@
 use axum::{
-    Json,
-    extract::{Extension, Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Extension, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
-use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     db,
     error::ApiError,
+    extract::{Json, Path, Query},
+    metadata,
     middleware::auth::BusinessContext,
-    models::transaction::{CreateTransactionRequest, TransactionResponse},
-    services::transaction_service::{TransactionError, create_transaction},
+    models::{
+        pagination::{Cursor, Page, normalize_limit},
+        transaction::{
+            BatchItemResult, BatchItemStatus, BatchResponse, CreateTransactionBatchRequest,
+            CreateTransactionRequest, ListTransactionsQuery, ReverseTransactionRequest,
+            TransactionFilter, TransactionResponse,
+        },
+    },
+    services::transaction_service::{
+        CreateTransactionOutcome, create_transaction, create_transaction_batch,
+        reverse_transaction,
+    },
     state::AppState,
 };
 
 pub async fn create_transaction_handler(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
+    headers: HeaderMap,
     Json(payload): Json<CreateTransactionRequest>,
-) -> impl IntoResponse {
-    let tx = match create_transaction(&state.pool, ctx.business_id, payload).await {
-        Ok(t) => t,
-        Err(TransactionError::BadRequest(msg)) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({ "error": msg }))).into_response();
+) -> Result<Response, ApiError> {
+    let idempotency_key = match headers.get("Idempotency-Key").map(|v| v.to_str()) {
+        None => None,
+        Some(Ok(v)) => Some(v.trim()),
+        Some(Err(_)) => {
+            return Err(ApiError::validation(
+                "Idempotency-Key",
+                "Idempotency-Key must be printable ASCII",
+            ));
         }
-        Err(TransactionError::NotFound) => {
-            return ApiError::NotFound.into_response();
-        }
-        Err(TransactionError::InsufficientFunds) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "insufficient funds" })),
-            )
-                .into_response();
+    };
+
+    let outcome = create_transaction(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+        idempotency_key,
+        payload,
+    )
+    .await?;
+
+    Ok(match outcome {
+        CreateTransactionOutcome::Created(tx) => {
+            (StatusCode::CREATED, Json(TransactionResponse::from(*tx))).into_response()
         }
-        Err(TransactionError::Internal) => {
-            return ApiError::InternalError.into_response();
+        CreateTransactionOutcome::Replayed(body) => {
+            (StatusCode::CREATED, Json(body)).into_response()
         }
-    };
+    })
+}
+
+// POST /api/transactions/batch: 201 if anything was committed, otherwise 200; per-item
+// outcomes are in `results` either way.
+pub async fn create_transaction_batch_handler(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Json(payload): Json<CreateTransactionBatchRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let outcome = create_transaction_batch(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+        payload.mode,
+        &payload.transactions,
+    )
+    .await?;
+
+    let committed = outcome.committed;
+    let results: Vec<BatchItemResult> = outcome
+        .results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| match result {
+            Ok(tx) => BatchItemResult {
+                index,
+                status: if committed {
+                    BatchItemStatus::Succeeded
+                } else {
+                    BatchItemStatus::RolledBack
+                },
+                transaction: committed.then(|| TransactionResponse::from(tx)),
+                error: None,
+            },
+            Err(err) => BatchItemResult {
+                index,
+                status: BatchItemStatus::Failed,
+                transaction: None,
+                error: Some(ApiError::from(err).body()),
+            },
+        })
+        .collect();
 
-    (StatusCode::CREATED, Json(TransactionResponse::from(tx))).into_response()
+    let succeeded = results
+        .iter()
+        .filter(|r| matches!(r.status, BatchItemStatus::Succeeded))
+        .count();
+    let failed = results
+        .iter()
+        .filter(|r| matches!(r.status, BatchItemStatus::Failed))
+        .count();
+
+    let status = if committed { StatusCode::CREATED } else { StatusCode::OK };
+    let resp = BatchResponse {
+        mode: payload.mode,
+        committed,
+        succeeded,
+        failed,
+        results,
+    };
+    Ok((status, Json(resp)))
 }
 
 pub async fn list_transactions(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
-) -> impl IntoResponse {
-    match db::list_transactions(&state.pool, ctx.business_id).await {
-        Ok(rows) => {
-            let resp: Vec<TransactionResponse> =
-                rows.into_iter().map(TransactionResponse::from).collect();
-            Json(resp).into_response()
-        }
-        Err(_) => ApiError::InternalError.into_response(),
+    Query(query): Query<ListTransactionsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Some(limit) = normalize_limit(query.limit) else {
+        return Err(ApiError::validation("limit", "limit must be between 1 and 200"));
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        None => None,
+        Some(Some(c)) => Some(c),
+        Some(None) => return Err(ApiError::validation("cursor", "invalid cursor")),
+    };
+
+    let amount_range_inverted = query
+        .min_amount
+        .zip(query.max_amount)
+        .is_some_and(|(min, max)| min > max);
+    if amount_range_inverted {
+        return Err(ApiError::validation("min_amount", "min_amount must be <= max_amount"));
     }
+
+    let metadata = match query.metadata.as_deref() {
+        None => None,
+        Some(raw) => Some(metadata::parse_filter(raw).ok_or_else(|| {
+            ApiError::validation("metadata", metadata::INVALID_FILTER_MESSAGE)
+        })?),
+    };
+
+    let filter = TransactionFilter {
+        tx_type: query.tx_type,
+        account_id: query.account_id,
+        min_amount: query.min_amount,
+        max_amount: query.max_amount,
+        created_from: query.created_from,
+        created_to: query.created_to,
+        external_reference: query.external_reference,
+        metadata,
+        allowed_account_ids: ctx.allowed_account_ids.clone(),
+    };
+
+    // Fetch one extra row to know whether another page exists.
+    let mut rows =
+        db::list_transactions(&state.pool, ctx.business_id, &filter, cursor, limit + 1).await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|t| {
+            Cursor {
+                created_at: t.created_at,
+                id: t.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    let data: Vec<TransactionResponse> = rows.into_iter().map(TransactionResponse::from).collect();
+    Ok(Json(Page { data, next_cursor }))
 }
 
 pub async fn get_transaction(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match db::get_transaction(&state.pool, ctx.business_id, id).await {
-        Ok(Some(row)) => Json(TransactionResponse::from(row)).into_response(),
-        Ok(None) => ApiError::NotFound.into_response(),
-        Err(_) => ApiError::InternalError.into_response(),
+) -> Result<impl IntoResponse, ApiError> {
+    let row = db::get_transaction(&state.pool, ctx.business_id, id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let out_of_scope = ctx
+        .allowed_account_ids
+        .as_deref()
+        .is_some_and(|ids| !row.touches_any(ids));
+    if out_of_scope {
+        return Err(ApiError::NotFound);
     }
+
+    Ok(Json(TransactionResponse::from(row)))
 }
 
+// POST /api/transactions/{id}/reverse: `{}` reverses everything not yet reversed.
+pub async fn reverse_transaction_handler(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<BusinessContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReverseTransactionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reversal = reverse_transaction(
+        &state.pool,
+        ctx.business_id,
+        ctx.allowed_account_ids.as_deref(),
+        id,
+        payload,
+    )
+    .await?;
 
+    Ok((StatusCode::CREATED, Json(TransactionResponse::from(reversal))))
+}
@@ -1,69 +1,72 @@
 use axum::{
-    Json,
-    extract::{Extension, Path, State},
+    extract::{Extension, State},
     http::StatusCode,
     response::IntoResponse,
 };
-use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     db,
     error::ApiError,
+    extract::{Json, Path},
     middleware::auth::BusinessContext,
     models::webhook::{CreateWebhookEndpointRequest, WebhookEndpointResponse},
-    services::webhook_service::{WebhookError, validate_create_endpoint},
+    services::webhook_service::{generate_endpoint_secret, validate_create_endpoint},
     state::AppState,
 };
 
+// Endpoints receive events for every account of the business, so account-restricted keys
+// cannot manage them.
+fn ensure_unrestricted(ctx: &BusinessContext) -> Result<(), ApiError> {
+    if ctx.allowed_account_ids.is_some() {
+        return Err(ApiError::Forbidden(
+            "account-restricted API keys cannot manage webhooks",
+        ));
+    }
+    Ok(())
+}
+
 pub async fn create_webhook_endpoint(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
     Json(payload): Json<CreateWebhookEndpointRequest>,
-) -> impl IntoResponse {
-    if let Err(e) = validate_create_endpoint(&payload.url) {
-        return match e {
-            WebhookError::BadRequest(msg) => {
-                (StatusCode::BAD_REQUEST, Json(json!({ "error": msg }))).into_response()
-            }
-            WebhookError::Internal => ApiError::InternalError.into_response(),
-        };
-    }
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_unrestricted(&ctx)?;
+    validate_create_endpoint(&payload.url)?;
 
-    match db::create_webhook_endpoint(&state.pool, ctx.business_id, &payload.url).await {
-        Ok(row) => (
-            StatusCode::CREATED,
-            Json(WebhookEndpointResponse::from(row)),
-        )
-            .into_response(),
-        Err(_) => ApiError::InternalError.into_response(),
-    }
+    let secret = generate_endpoint_secret();
+
+    let row =
+        db::create_webhook_endpoint(&state.pool, ctx.business_id, &payload.url, &secret).await?;
+
+    let mut resp = WebhookEndpointResponse::from(row);
+    resp.secret = Some(secret);
+    Ok((StatusCode::CREATED, Json(resp)))
 }
 
 pub async fn list_webhook_endpoints(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
-) -> impl IntoResponse {
-    match db::list_webhook_endpoints(&state.pool, ctx.business_id).await {
-        Ok(rows) => {
-            let resp: Vec<WebhookEndpointResponse> =
-                rows.into_iter().map(WebhookEndpointResponse::from).collect();
-            Json(resp).into_response()
-        }
-        Err(_) => ApiError::InternalError.into_response(),
-    }
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_unrestricted(&ctx)?;
+
+    let rows = db::list_webhook_endpoints(&state.pool, ctx.business_id).await?;
+
+    let resp: Vec<WebhookEndpointResponse> =
+        rows.into_iter().map(WebhookEndpointResponse::from).collect();
+    Ok(Json(resp))
 }
 
 pub async fn delete_webhook_endpoint(
     State(state): State<AppState>,
     Extension(ctx): Extension<BusinessContext>,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match db::deactivate_webhook_endpoint(&state.pool, ctx.business_id, id).await {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
-        Ok(false) => ApiError::NotFound.into_response(),
-        Err(_) => ApiError::InternalError.into_response(),
-    }
-}
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_unrestricted(&ctx)?;
 
+    if !db::deactivate_webhook_endpoint(&state.pool, ctx.business_id, id).await? {
+        return Err(ApiError::NotFound);
+    }
 
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,9 @@
 pub mod config;
+pub mod currency;
+pub mod metadata;
 pub mod db;
 pub mod error;
+pub mod extract;
 pub mod models;
 pub mod services;
 pub mod state;
@@ -1,7 +1,7 @@
 use axum::{
     Router,
-    middleware::from_fn_with_state,
-    routing::{delete, get, post},
+    middleware::{from_fn, from_fn_with_state},
+    routing::{MethodRouter, delete, get, post, put},
 };
 
 use dodo_assign::{
@@ -9,32 +9,125 @@ use dodo_assign::{
     db::pool::create_pool,
     handlers::{
         accounts,
+        api_keys,
+        fx,
+        holds,
+        ledger,
+        scheduled_transactions,
         health::{db_health_check, health_check},
         transactions,
         webhooks,
     },
-    middleware::auth::api_key_auth,
+    middleware::{
+        auth::{api_key_auth, require_scope},
+        request_id::request_id,
+    },
+    models::api_key::Scope,
+    services::api_key_service::report_key_secrets,
     state::AppState,
-    worker::webhook_worker,
+    worker::{balance_snapshot_worker, hold_worker, schedule_worker, webhook_worker},
 };
 
+fn scoped(route: MethodRouter<AppState>, scope: Scope) -> MethodRouter<AppState> {
+    route.route_layer(from_fn_with_state(scope, require_scope))
+}
+
 fn create_app(state: AppState) -> Router {
+    use Scope::*;
+
     let protected = Router::new()
-        .route("/create-account", post(accounts::create_account))
-        .route("/accounts", get(accounts::list_accounts))
-        .route("/accounts/{id}", get(accounts::get_account))
-        .route("/transactions", post(transactions::create_transaction_handler))
-        .route("/transactions", get(transactions::list_transactions))
-        .route("/transactions/{id}", get(transactions::get_transaction))
-        .route("/webhooks", post(webhooks::create_webhook_endpoint))
-        .route("/webhooks", get(webhooks::list_webhook_endpoints))
-        .route("/webhooks/{id}", delete(webhooks::delete_webhook_endpoint))
+        .route("/create-account", scoped(post(accounts::create_account), AccountsWrite))
+        .route("/accounts", scoped(get(accounts::list_accounts), AccountsRead))
+        .route("/accounts/{id}", scoped(get(accounts::get_account), AccountsRead))
+        .route("/accounts/{id}/freeze", scoped(post(accounts::freeze_account), AccountsWrite))
+        .route(
+            "/accounts/{id}/unfreeze",
+            scoped(post(accounts::unfreeze_account), AccountsWrite),
+        )
+        .route("/accounts/{id}/close", scoped(post(accounts::close_account), AccountsWrite))
+        .route(
+            "/accounts/{id}/balance",
+            scoped(get(accounts::get_account_balance_at), AccountsRead),
+        )
+        .route(
+            "/accounts/{id}/balances",
+            scoped(get(accounts::list_account_daily_balances), AccountsRead),
+        )
+        .route(
+            "/accounts/{id}/overdraft-limit",
+            scoped(put(accounts::set_overdraft_limit), AccountsWrite),
+        )
+        .route("/accounts/{id}/limits", scoped(put(accounts::set_account_limits), AccountsWrite))
+        .route(
+            "/accounts/{id}/overdraft-limit-changes",
+            scoped(get(accounts::list_overdraft_limit_changes), AccountsRead),
+        )
+        .route(
+            "/accounts/{id}/transactions",
+            scoped(get(accounts::list_account_transactions), AccountsRead),
+        )
+        .route(
+            "/transactions",
+            scoped(post(transactions::create_transaction_handler), TransactionsWrite),
+        )
+        .route(
+            "/transactions/batch",
+            scoped(post(transactions::create_transaction_batch_handler), TransactionsWrite),
+        )
+        .route("/transactions", scoped(get(transactions::list_transactions), TransactionsRead))
+        .route("/transactions/{id}", scoped(get(transactions::get_transaction), TransactionsRead))
+        .route(
+            "/transactions/{id}/reverse",
+            scoped(post(transactions::reverse_transaction_handler), TransactionsWrite),
+        )
+        .route("/api-keys", scoped(post(api_keys::create_api_key), ApiKeysManage))
+        .route("/api-keys", scoped(get(api_keys::list_api_keys), ApiKeysManage))
+        .route("/api-keys/{id}", scoped(delete(api_keys::revoke_api_key), ApiKeysManage))
+        .route("/api-keys/{id}/rotate", scoped(post(api_keys::rotate_api_key), ApiKeysManage))
+        .route("/fx/rates", scoped(get(fx::list_fx_rates), TransactionsRead))
+        .route("/fx/rates/{base}/{quote}", scoped(put(fx::set_fx_rate), FxManage))
+        .route("/fx/rates/{base}/{quote}", scoped(delete(fx::delete_fx_rate), FxManage))
+        .route("/fx/quotes", scoped(post(fx::create_fx_quote), TransactionsWrite))
+        .route("/fx/quotes/{id}", scoped(get(fx::get_fx_quote), TransactionsRead))
+        .route("/holds", scoped(post(holds::create_hold), TransactionsWrite))
+        .route("/holds/{id}", scoped(get(holds::get_hold), TransactionsRead))
+        .route("/holds/{id}/capture", scoped(post(holds::capture_hold), TransactionsWrite))
+        .route("/holds/{id}/void", scoped(post(holds::void_hold), TransactionsWrite))
+        .route(
+            "/accounts/{id}/holds",
+            scoped(get(holds::list_account_holds), TransactionsRead),
+        )
+        .route(
+            "/scheduled-transactions",
+            scoped(post(scheduled_transactions::create_scheduled_transaction), TransactionsWrite),
+        )
+        .route(
+            "/scheduled-transactions",
+            scoped(get(scheduled_transactions::list_scheduled_transactions), TransactionsRead),
+        )
+        .route(
+            "/scheduled-transactions/{id}",
+            scoped(get(scheduled_transactions::get_scheduled_transaction), TransactionsRead),
+        )
+        .route(
+            "/scheduled-transactions/{id}/runs",
+            scoped(get(scheduled_transactions::list_scheduled_transaction_runs), TransactionsRead),
+        )
+        .route(
+            "/scheduled-transactions/{id}/cancel",
+            scoped(post(scheduled_transactions::cancel_scheduled_transaction), TransactionsWrite),
+        )
+        .route("/ledger/verify", scoped(get(ledger::verify_ledger), AccountsRead))
+        .route("/webhooks", scoped(post(webhooks::create_webhook_endpoint), WebhooksManage))
+        .route("/webhooks", scoped(get(webhooks::list_webhook_endpoints), WebhooksManage))
+        .route("/webhooks/{id}", scoped(delete(webhooks::delete_webhook_endpoint), WebhooksManage))
         .layer(from_fn_with_state(state.clone(), api_key_auth));
 
     Router::new()
         .route("/health", get(health_check))
         .route("/health/db", get(db_health_check))
         .nest("/api", protected)
+        .layer(from_fn(request_id))
         .with_state(state.clone())
 }
 
@@ -53,9 +146,23 @@ async fn main() {
         .expect("failed to run migrations");
     println!("migrations complete");
 
+    if std::env::args().nth(1).as_deref() == Some("report-key-secrets") {
+        let report = report_key_secrets(&pool, &config.hmac_secret, &config.previous_hmac_secrets)
+            .await
+            .expect("failed to count api keys");
+
+        println!("active api keys on HMAC_SECRET: {}", report.primary);
+        for (i, count) in report.previous.iter().enumerate() {
+            println!("active api keys on HMAC_PREVIOUS_SECRETS[{i}]: {count}");
+        }
+        println!("active api keys on unknown secret: {}", report.unknown);
+        return;
+    }
+
     let state = AppState {
         pool,
         hmac_secret: config.hmac_secret,
+        previous_hmac_secrets: config.previous_hmac_secrets,
     };
 
     let worker_state = state.clone();
@@ -63,6 +170,21 @@ async fn main() {
         webhook_worker::run(worker_state.pool).await;
     });
 
+    let hold_worker_state = state.clone();
+    tokio::spawn(async move {
+        hold_worker::run(hold_worker_state.pool).await;
+    });
+
+    let schedule_worker_state = state.clone();
+    tokio::spawn(async move {
+        schedule_worker::run(schedule_worker_state.pool).await;
+    });
+
+    let snapshot_worker_state = state.clone();
+    tokio::spawn(async move {
+        balance_snapshot_worker::run(snapshot_worker_state.pool).await;
+    });
+
     let app = create_app(state);
 
     let bind_addr = format!("0.0.0.0:{}", config.server_port);
@@ -0,0 +1,54 @@
+// Free-form `metadata` and `external_reference` of accounts and transactions.
+//
+// Metadata is a JSON object stored as-is; the limits below keep rows and webhook payloads
+// small. List endpoints filter on it with `?metadata={"key":"value"}`, which matches rows
+// whose metadata has exactly these top-level values.
+
+use serde_json::{Map, Value};
+
+pub type Metadata = Map<String, Value>;
+
+pub const MAX_KEYS: usize = 50;
+pub const MAX_KEY_LEN: usize = 40;
+// Of the serialized object.
+pub const MAX_BYTES: usize = 8 * 1024;
+pub const MAX_EXTERNAL_REFERENCE_LEN: usize = 255;
+
+pub fn validate(metadata: &Metadata) -> Result<(), &'static str> {
+    if metadata.len() > MAX_KEYS {
+        return Err("metadata must have at most 50 keys");
+    }
+    if metadata.keys().any(|k| k.is_empty() || k.len() > MAX_KEY_LEN) {
+        return Err("metadata keys must be 1-40 characters");
+    }
+    let size = serde_json::to_vec(metadata).map(|v| v.len()).unwrap_or(usize::MAX);
+    if size > MAX_BYTES {
+        return Err("metadata must be at most 8192 bytes when serialized");
+    }
+    Ok(())
+}
+
+pub fn validate_external_reference(reference: &str) -> Result<(), &'static str> {
+    if reference.is_empty() || reference.len() > MAX_EXTERNAL_REFERENCE_LEN {
+        return Err("external_reference must be 1-255 characters");
+    }
+    Ok(())
+}
+
+pub const INVALID_FILTER_MESSAGE: &str =
+    "metadata must be a non-empty JSON object of string, number or boolean values";
+
+// Parses the `metadata` query parameter: a JSON object of scalar values (string, number or
+// boolean), so that containment is an exact match on each key.
+pub fn parse_filter(input: &str) -> Option<Metadata> {
+    let Ok(Value::Object(filter)) = serde_json::from_str(input) else {
+        return None;
+    };
+    let scalars = filter
+        .values()
+        .all(|v| matches!(v, Value::String(_) | Value::Number(_) | Value::Bool(_)));
+    if filter.is_empty() || !scalars {
+        return None;
+    }
+    Some(filter)
+}
@@ -4,12 +4,12 @@ use axum::{
     response::Response,
 };
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 
 use crate::{
     db,
     error::ApiError,
-    models::api_key::ApiKeyLookup,
+    models::api_key::{ApiKeyLookup, Scope},
     state::AppState,
 };
 
@@ -17,6 +17,21 @@ use crate::{
 pub struct BusinessContext {
     pub business_id: uuid::Uuid,
     pub api_key_id: uuid::Uuid,
+    pub scopes: Vec<Scope>,
+    // None = every account of the business.
+    pub allowed_account_ids: Option<Vec<uuid::Uuid>>,
+}
+
+impl BusinessContext {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn can_access_account(&self, account_id: uuid::Uuid) -> bool {
+        self.allowed_account_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&account_id))
+    }
 }
 
 pub async fn api_key_auth(
@@ -34,23 +49,92 @@ pub async fn api_key_auth(
         .filter(|v| !v.is_empty())
         .ok_or(ApiError::Unauthorized)?;
 
-    let key_hash = hmac_sha256_hex(&state.hmac_secret, raw_api_key);
+    let primary_hash = hmac_sha256_hex(&state.hmac_secret, raw_api_key);
+    let primary_fingerprint = hmac_secret_fingerprint(&state.hmac_secret);
 
-    let api_key: ApiKeyLookup = db::find_active_api_key_by_hash(&state.pool, &key_hash)
+    let api_key: ApiKeyLookup = match db::find_active_api_key_by_hash(&state.pool, &primary_hash)
         .await
         .map_err(|_| ApiError::InternalError)?
-        .ok_or(ApiError::Unauthorized)?;
+    {
+        Some(key) => {
+            // Legacy keys have no fingerprint yet; record it on first use.
+            if key.hmac_secret_fingerprint.as_deref() != Some(primary_fingerprint.as_str()) {
+                rehash_best_effort(&state, &key, &primary_hash, &primary_fingerprint).await;
+            }
+            key
+        }
+        None => {
+            // Fall back to secrets from before an HMAC_SECRET rotation, then move the key
+            // to the primary secret so the old one can eventually be retired.
+            let mut found = None;
+            for secret in &state.previous_hmac_secrets {
+                let old_hash = hmac_sha256_hex(secret, raw_api_key);
+                if let Some(key) = db::find_active_api_key_by_hash(&state.pool, &old_hash)
+                    .await
+                    .map_err(|_| ApiError::InternalError)?
+                {
+                    rehash_best_effort(&state, &key, &primary_hash, &primary_fingerprint).await;
+                    found = Some(key);
+                    break;
+                }
+            }
+            found.ok_or(ApiError::Unauthorized)?
+        }
+    };
+
+    if let Err(err) = db::touch_api_key_last_used(&state.pool, api_key.id).await {
+        eprintln!("failed to update api key last_used_at: {err}");
+    }
 
     req.extensions_mut().insert(BusinessContext {
         business_id: api_key.business_id,
         api_key_id: api_key.id,
+        scopes: api_key.scopes,
+        allowed_account_ids: api_key.allowed_account_ids,
     });
 
     // println!("here 2");
     Ok(next.run(req).await)
 }
 
-fn hmac_sha256_hex(secret: &str, msg: &str) -> String {
+// Per-route guard, layered inside `api_key_auth`:
+// `.route_layer(from_fn_with_state(Scope::AccountsRead, require_scope))`.
+pub async fn require_scope(
+    State(scope): State<Scope>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let ctx = req
+        .extensions()
+        .get::<BusinessContext>()
+        .ok_or(ApiError::Unauthorized)?;
+
+    if !ctx.has_scope(scope) {
+        return Err(ApiError::Forbidden(
+            "API key is missing the scope required for this route",
+        ));
+    }
+
+    Ok(next.run(req).await)
+}
+
+async fn rehash_best_effort(
+    state: &AppState,
+    key: &ApiKeyLookup,
+    new_hash: &str,
+    fingerprint: &str,
+) {
+    if let Err(err) = db::rehash_api_key(&state.pool, key.id, new_hash, fingerprint).await {
+        eprintln!("failed to rehash api key {}: {err}", key.id);
+    }
+}
+
+// Short, non-reversible identifier of an HMAC secret, stored next to each key hash.
+pub(crate) fn hmac_secret_fingerprint(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))[..16].to_string()
+}
+
+pub(crate) fn hmac_sha256_hex(secret: &str, msg: &str) -> String {
     type HmacSha256 = Hmac<Sha256>;
 
     let mut mac =
@@ -0,0 +1,47 @@
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use rand::Rng;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request currently being handled, if called from inside `request_id`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+fn generate_request_id() -> String {
+    let mut bytes = [0u8; 12];
+    rand::rng().fill(&mut bytes);
+    format!("req_{}", hex::encode(bytes))
+}
+
+// Reuses a caller-supplied id (e.g. from a load balancer) when it is safe to echo back.
+fn incoming_request_id(req: &Request) -> Option<String> {
+    let id = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    let valid = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+// Outermost layer: tags every response with `X-Request-Id` and makes the id available to
+// `ApiError` so error bodies carry it too.
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let id = incoming_request_id(&req).unwrap_or_else(generate_request_id);
+
+    let mut res = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    res
+}
@@ -1,6 +1,78 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::currency;
+use crate::metadata::Metadata;
+use crate::models::transaction::{Transaction, TransactionType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    // Can receive money but not send it.
+    Frozen,
+    // Final; no movement at all.
+    Closed,
+}
+
+impl AccountStatus {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Frozen => "frozen",
+            AccountStatus::Closed => "closed",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(AccountStatus::Active),
+            "frozen" => Some(AccountStatus::Frozen),
+            "closed" => Some(AccountStatus::Closed),
+            _ => None,
+        }
+    }
+}
+
+// Risk limits on money leaving an account; None = no limit.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountLimits {
+    // Largest single debit or outgoing transfer.
+    #[serde(default)]
+    pub max_transaction_amount: Option<i64>,
+    // Debits and outgoing transfers per UTC day.
+    #[serde(default)]
+    pub max_daily_outflow_count: Option<i64>,
+    // Total debited per UTC day.
+    #[serde(default)]
+    pub max_daily_outflow_amount: Option<i64>,
+    // `balance` minus holds may not go below this; also rules out any overdraft.
+    #[serde(default)]
+    pub min_balance: Option<i64>,
+}
+
+// The limit a rejected transaction would have broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountLimit {
+    MaxTransactionAmount,
+    MaxDailyOutflowCount,
+    MaxDailyOutflowAmount,
+    MinBalance,
+}
+
+impl AccountLimit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountLimit::MaxTransactionAmount => "max_transaction_amount",
+            AccountLimit::MaxDailyOutflowCount => "max_daily_outflow_count",
+            AccountLimit::MaxDailyOutflowAmount => "max_daily_outflow_amount",
+            AccountLimit::MinBalance => "min_balance",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Account {
     pub id: Uuid,
@@ -8,12 +80,39 @@ pub struct Account {
     pub name: String,
     pub currency: String,
     pub balance: i64,
+    // `balance` minus active holds; debits and transfers may spend it plus `overdraft_limit`.
+    pub available_balance: i64,
+    pub status: AccountStatus,
+    pub status_changed_at: Option<DateTime<Utc>>,
+    pub metadata: Metadata,
+    pub external_reference: Option<String>,
+    // How far below zero `balance` may go; 0 for most accounts.
+    pub overdraft_limit: i64,    pub limits: AccountLimits,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateAccountRequest {
     pub name: String,
     pub currency: String,
+    pub metadata: Option<Metadata>,
+    pub external_reference: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListAccountsQuery {
+    pub external_reference: Option<String>,
+    // JSON object, e.g. {"customer_id":"c_42"}.
+    pub metadata: Option<String>,
+}
+
+// Validated filters for db::list_accounts.
+#[derive(Debug, Clone, Default)]
+pub struct AccountFilter {
+    pub external_reference: Option<String>,
+    pub metadata: Option<Metadata>,
+    // Account-restricted API keys: only these accounts.
+    pub allowed_account_ids: Option<Vec<Uuid>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -22,17 +121,179 @@ pub struct AccountResponse {
     pub name: String,
     pub currency: String,
     pub balance: i64,
+    // `balance` as a decimal string using the currency exponent, e.g. "10.50".
+    pub balance_decimal: Option<String>,
+    pub available_balance: i64,
+    pub available_balance_decimal: Option<String>,
+    pub status: AccountStatus,
+    pub status_changed_at: Option<DateTime<Utc>>,
+    pub metadata: Metadata,
+    pub external_reference: Option<String>,
+    pub overdraft_limit: i64,    pub limits: AccountLimits,
 }
 
 impl From<Account> for AccountResponse {
     fn from(a: Account) -> Self {
+        let currency = a.currency.trim().to_string();
         Self {
             id: a.id,
             name: a.name,
-            currency: a.currency.trim().to_string(),
+            balance_decimal: currency::format_amount(a.balance, &currency),
+            available_balance_decimal: currency::format_amount(a.available_balance, &currency),
+            currency,
             balance: a.balance,
+            available_balance: a.available_balance,
+            status: a.status,
+            status_changed_at: a.status_changed_at,
+            metadata: a.metadata,
+            external_reference: a.external_reference,
+            overdraft_limit: a.overdraft_limit,
+            limits: a.limits,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetOverdraftLimitRequest {
+    // In minor units; 0 removes the overdraft.
+    pub overdraft_limit: i64,
+}
+
+// Audit record of one change to an account's overdraft limit.
+#[derive(Debug, Clone)]
+pub struct OverdraftLimitChange {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub previous_limit: i64,
+    pub new_limit: i64,
+    // The API key that made the change.
+    pub api_key_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverdraftLimitChangeResponse {
+    pub id: Uuid,
+    pub previous_limit: i64,
+    pub new_limit: i64,
+    pub api_key_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OverdraftLimitChange> for OverdraftLimitChangeResponse {
+    fn from(c: OverdraftLimitChange) -> Self {
+        Self {
+            id: c.id,
+            previous_limit: c.previous_limit,
+            new_limit: c.new_limit,
+            api_key_id: c.api_key_id,
+            created_at: c.created_at,
         }
     }
 }
 
+// One line of an account statement: a transaction seen from this account's side.
+#[derive(Debug, Clone)]
+pub struct StatementEntry {
+    pub seq: i64,
+    pub transaction: Transaction,
+    // Signed: positive when money came into this account.
+    pub amount: i64,
+    pub balance_after: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatementEntryResponse {
+    pub transaction_id: Uuid,
+    #[serde(rename = "type")]
+    pub tx_type: TransactionType,
+    pub source_account_id: Option<Uuid>,
+    pub dest_account_id: Option<Uuid>,
+    pub amount: i64,
+    pub balance_after: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<StatementEntry> for StatementEntryResponse {
+    fn from(e: StatementEntry) -> Self {
+        Self {
+            transaction_id: e.transaction.id,
+            tx_type: e.transaction.tx_type,
+            source_account_id: e.transaction.source_account_id,
+            dest_account_id: e.transaction.dest_account_id,
+            amount: e.amount,
+            balance_after: e.balance_after,
+            created_at: e.transaction.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceAtQuery {
+    // Defaults to now.
+    pub at: Option<DateTime<Utc>>,
+}
 
+#[derive(Debug, Serialize)]
+pub struct BalanceAtResponse {
+    pub account_id: Uuid,
+    pub currency: String,
+    pub at: DateTime<Utc>,
+    // Ledger balance: every transaction created before `at`; holds are not subtracted.
+    pub balance: i64,
+    pub balance_decimal: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyBalancesQuery {
+    // Inclusive UTC dates, e.g. 2026-01-31.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// Balance at the end of `date` (midnight UTC).
+#[derive(Debug, Clone, Copy)]
+pub struct DailyBalance {
+    pub date: NaiveDate,
+    pub balance: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyBalanceResponse {
+    pub date: NaiveDate,
+    pub balance: i64,
+    pub balance_decimal: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyBalancesResponse {
+    pub account_id: Uuid,
+    pub currency: String,
+    pub balances: Vec<DailyBalanceResponse>,
+}
+
+impl DailyBalancesResponse {
+    pub fn new(account: &Account, balances: Vec<DailyBalance>) -> Self {
+        let currency = account.currency.trim().to_string();
+        let balances = balances
+            .into_iter()
+            .map(|b| DailyBalanceResponse {
+                date: b.date,
+                balance: b.balance,
+                balance_decimal: currency::format_amount(b.balance, &currency),
+            })
+            .collect();
+        Self {
+            account_id: account.id,
+            currency,
+            balances,
+        }
+    }
+}
@@ -1,9 +1,118 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    AccountsRead,
+    AccountsWrite,
+    TransactionsRead,
+    TransactionsWrite,
+    WebhooksManage,
+    ApiKeysManage,
+    FxManage,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 7] = [
+        Scope::AccountsRead,
+        Scope::AccountsWrite,
+        Scope::TransactionsRead,
+        Scope::TransactionsWrite,
+        Scope::WebhooksManage,
+        Scope::ApiKeysManage,
+        Scope::FxManage,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::AccountsRead => "accounts:read",
+            Scope::AccountsWrite => "accounts:write",
+            Scope::TransactionsRead => "transactions:read",
+            Scope::TransactionsWrite => "transactions:write",
+            Scope::WebhooksManage => "webhooks:manage",
+            Scope::ApiKeysManage => "api_keys:manage",
+            Scope::FxManage => "fx:manage",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+// Unknown scope strings (e.g. from a newer deployment) are dropped rather than failing auth.
+pub fn parse_scopes(raw: &[String]) -> Vec<Scope> {
+    raw.iter().filter_map(|s| Scope::parse(s)).collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiKeyLookup {
     pub id: Uuid,
     pub business_id: Uuid,
+    pub hmac_secret_fingerprint: Option<String>,
+    pub scopes: Vec<Scope>,
+    pub allowed_account_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub key_prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+    pub allowed_account_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyRequest {
+    pub scopes: Vec<String>,
+    // Omit for access to every account of the business.
+    pub account_ids: Option<Vec<Uuid>>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RotateApiKeyRequest {
+    // How long the old key keeps working; defaults to 24h.
+    pub overlap_seconds: Option<i64>,
+}
 
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+    pub account_ids: Option<Vec<Uuid>>,
+    // Raw key; only returned once, when the key is created or rotated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(k: ApiKey) -> Self {
+        Self {
+            id: k.id,
+            prefix: k.key_prefix,
+            created_at: k.created_at,
+            last_used_at: k.last_used_at,
+            revoked_at: k.revoked_at,
+            scopes: k.scopes,
+            account_ids: k.allowed_account_ids,
+            key: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateApiKeyResponse {
+    pub new_key: ApiKeyResponse,
+    pub old_key: ApiKeyResponse,
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::currency;
+
+#[derive(Debug, Clone)]
+pub struct FxRateEntry {
+    pub base_currency: String,
+    pub quote_currency: String,
+    // Decimal string without trailing zeros, e.g. "0.92".
+    pub rate: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetFxRateRequest {
+    // Decimal string to avoid float rounding, e.g. "0.9215".
+    pub rate: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FxRateResponse {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<FxRateEntry> for FxRateResponse {
+    fn from(r: FxRateEntry) -> Self {
+        Self {
+            base_currency: r.base_currency.trim().to_string(),
+            quote_currency: r.quote_currency.trim().to_string(),
+            rate: r.rate,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+// Destination side of a cross-currency transfer; the source side is the transaction's
+// `amount`/`currency`.
+#[derive(Debug, Clone)]
+pub struct FxConversion {
+    pub rate: String,
+    pub dest_amount: i64,
+    pub dest_currency: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FxConversionResponse {
+    pub rate: String,
+    pub dest_amount: i64,
+    pub dest_currency: String,
+    pub dest_amount_decimal: Option<String>,
+}
+
+impl From<FxConversion> for FxConversionResponse {
+    fn from(fx: FxConversion) -> Self {
+        let dest_currency = fx.dest_currency.trim().to_string();
+        Self {
+            rate: fx.rate,
+            dest_amount: fx.dest_amount,
+            dest_amount_decimal: currency::format_amount(fx.dest_amount, &dest_currency),
+            dest_currency,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FxQuote {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub source_account_id: Uuid,
+    pub dest_account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub conversion: FxConversion,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateFxQuoteRequest {
+    pub source_account_id: Uuid,
+    pub dest_account_id: Uuid,
+    // In the source account's minor units.
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FxQuoteResponse {
+    pub id: Uuid,
+    pub source_account_id: Uuid,
+    pub dest_account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub amount_decimal: Option<String>,
+    pub rate: String,
+    pub dest_amount: i64,
+    pub dest_currency: String,
+    pub dest_amount_decimal: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<FxQuote> for FxQuoteResponse {
+    fn from(q: FxQuote) -> Self {
+        let currency = q.currency.trim().to_string();
+        let fx = FxConversionResponse::from(q.conversion);
+        Self {
+            id: q.id,
+            source_account_id: q.source_account_id,
+            dest_account_id: q.dest_account_id,
+            amount: q.amount,
+            amount_decimal: currency::format_amount(q.amount, &currency),
+            currency,
+            rate: fx.rate,
+            dest_amount: fx.dest_amount,
+            dest_currency: fx.dest_currency,
+            dest_amount_decimal: fx.dest_amount_decimal,
+            expires_at: q.expires_at,
+            used_at: q.used_at,
+            transaction_id: q.transaction_id,
+            created_at: q.created_at,
+        }
+    }
+}
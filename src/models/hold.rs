@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::currency;
+use crate::models::transaction::TransactionResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoldStatus {
+    Active,
+    Captured,
+    Voided,
+    Expired,
+}
+
+impl HoldStatus {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            HoldStatus::Active => "active",
+            HoldStatus::Captured => "captured",
+            HoldStatus::Voided => "voided",
+            HoldStatus::Expired => "expired",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(HoldStatus::Active),
+            "captured" => Some(HoldStatus::Captured),
+            "voided" => Some(HoldStatus::Voided),
+            "expired" => Some(HoldStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hold {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub status: HoldStatus,
+    // Set once captured; the rest of `amount` was released.
+    pub captured_amount: Option<i64>,
+    pub transaction_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateHoldRequest {
+    pub account_id: Uuid,
+    pub amount: i64,
+    // Defaults to 7 days.
+    pub expires_in_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaptureHoldRequest {
+    // Defaults to the full hold amount.
+    pub amount: Option<i64>,
+    // Captures into a transfer to this account instead of a debit.
+    pub dest_account_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HoldResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub amount: i64,
+    pub currency: String,
+    pub amount_decimal: Option<String>,
+    pub status: HoldStatus,
+    pub captured_amount: Option<i64>,
+    pub transaction_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Hold> for HoldResponse {
+    fn from(h: Hold) -> Self {
+        let currency = h.currency.trim().to_string();
+        Self {
+            id: h.id,
+            account_id: h.account_id,
+            amount: h.amount,
+            amount_decimal: currency::format_amount(h.amount, &currency),
+            currency,
+            status: h.status,
+            captured_amount: h.captured_amount,
+            transaction_id: h.transaction_id,
+            expires_at: h.expires_at,
+            released_at: h.released_at,
+            created_at: h.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureHoldResponse {
+    pub hold: HoldResponse,
+    pub transaction: TransactionResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListHoldsQuery {
+    pub status: Option<HoldStatus>,
+}
@@ -0,0 +1,47 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct AccountLedgerBalance {
+    pub account_id: Uuid,
+    pub name: String,
+    pub currency: String,
+    pub is_system: bool,
+    // accounts.balance (not maintained for system accounts)
+    pub cached_balance: i64,
+    // SUM(postings.amount)
+    pub ledger_balance: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountVerificationResponse {
+    pub account_id: Uuid,
+    pub name: String,
+    pub currency: String,
+    pub system: bool,
+    pub cached_balance: Option<i64>,
+    pub ledger_balance: i64,
+    pub consistent: bool,
+}
+
+impl From<AccountLedgerBalance> for AccountVerificationResponse {
+    fn from(b: AccountLedgerBalance) -> Self {
+        // System accounts have no cached balance to compare against.
+        let cached_balance = (!b.is_system).then_some(b.cached_balance);
+        Self {
+            account_id: b.account_id,
+            name: b.name,
+            currency: b.currency.trim().to_string(),
+            system: b.is_system,
+            consistent: cached_balance.is_none_or(|c| c == b.ledger_balance),
+            cached_balance,
+            ledger_balance: b.ledger_balance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerVerificationResponse {
+    pub consistent: bool,
+    pub accounts: Vec<AccountVerificationResponse>,
+}
@@ -1,5 +1,10 @@
 pub mod account;
 pub mod api_key;
+pub mod fx;
+pub mod hold;
+pub mod ledger;
+pub mod pagination;
+pub mod scheduled_transaction;
 pub mod transaction;
 pub mod webhook;
 
@@ -0,0 +1,64 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+pub const MAX_PAGE_LIMIT: i64 = 200;
+
+// Keyset position: the last row of the previous page, ordered by (created_at DESC, id DESC).
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    // Opaque to clients: hex of "<rfc3339 micros>|<uuid>".
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}",
+            self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.id
+        );
+        hex::encode(raw)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let raw = String::from_utf8(hex::decode(s).ok()?).ok()?;
+        let (ts, id) = raw.split_once('|')?;
+        Some(Self {
+            created_at: DateTime::parse_from_rfc3339(ts).ok()?.with_timezone(&Utc),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+// Keyset position for ledger statements, ordered by postings.seq DESC.
+#[derive(Debug, Clone, Copy)]
+pub struct SeqCursor(pub i64);
+
+impl SeqCursor {
+    pub fn encode(&self) -> String {
+        hex::encode(format!("seq|{}", self.0))
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let raw = String::from_utf8(hex::decode(s).ok()?).ok()?;
+        let seq = raw.strip_prefix("seq|")?.parse().ok()?;
+        Some(Self(seq))
+    }
+}
+
+pub fn normalize_limit(limit: Option<i64>) -> Option<i64> {
+    match limit {
+        None => Some(DEFAULT_PAGE_LIMIT),
+        Some(l) if (1..=MAX_PAGE_LIMIT).contains(&l) => Some(l),
+        Some(_) => None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,283 @@
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::transaction::{CreateTransactionRequest, TransactionType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ScheduleFrequency {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            ScheduleFrequency::Daily => "daily",
+            ScheduleFrequency::Weekly => "weekly",
+            ScheduleFrequency::Monthly => "monthly",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(ScheduleFrequency::Daily),
+            "weekly" => Some(ScheduleFrequency::Weekly),
+            "monthly" => Some(ScheduleFrequency::Monthly),
+            _ => None,
+        }
+    }
+
+    // Occurrence `n` (0-based) of a schedule starting at `start`. Always computed from
+    // `start`, so a monthly schedule on the 31st runs on the last day of shorter months
+    // and goes back to the 31st afterwards.
+    pub fn occurrence(self, start: DateTime<Utc>, n: u32) -> Option<DateTime<Utc>> {
+        match self {
+            ScheduleFrequency::Daily => start.checked_add_signed(Duration::days(n.into())),
+            ScheduleFrequency::Weekly => start.checked_add_signed(Duration::weeks(n.into())),
+            ScheduleFrequency::Monthly => start.checked_add_months(Months::new(n)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleStatus {
+    Active,
+    Completed,
+    Cancelled,
+}
+
+impl ScheduleStatus {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            ScheduleStatus::Active => "active",
+            ScheduleStatus::Completed => "completed",
+            ScheduleStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(ScheduleStatus::Active),
+            "completed" => Some(ScheduleStatus::Completed),
+            "cancelled" => Some(ScheduleStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledRunStatus {
+    Succeeded,
+    Failed,
+}
+
+impl ScheduledRunStatus {
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            ScheduledRunStatus::Succeeded => "succeeded",
+            ScheduledRunStatus::Failed => "failed",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "succeeded" => Some(ScheduledRunStatus::Succeeded),
+            "failed" => Some(ScheduledRunStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledTransaction {
+    pub id: Uuid,
+    pub business_id: Uuid,
+    pub tx_type: TransactionType,
+    pub source_account_id: Option<Uuid>,
+    pub dest_account_id: Option<Uuid>,
+    pub amount: i64,
+    // None for a one-off schedule.
+    pub frequency: Option<ScheduleFrequency>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: Option<DateTime<Utc>>,
+    pub status: ScheduleStatus,
+    // Occurrences executed so far; also the index of the next one.
+    pub run_count: i32,
+    // None once completed or cancelled.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ScheduledTransaction {
+    // The request each run executes.
+    pub fn transaction_request(&self) -> CreateTransactionRequest {
+        CreateTransactionRequest {
+            tx_type: self.tx_type,
+            amount: self.amount,
+            source_account_id: self.source_account_id,
+            dest_account_id: self.dest_account_id,
+            fx_rate: None,
+            quote_id: None,
+            metadata: None,
+            external_reference: None,
+            description: None,
+            counterparty: None,
+        }
+    }
+
+    pub fn account_ids(&self) -> impl Iterator<Item = Uuid> {
+        self.source_account_id.into_iter().chain(self.dest_account_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledTransactionRun {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    pub occurrence: i32,
+    pub scheduled_for: DateTime<Utc>,
+    pub status: ScheduledRunStatus,
+    pub transaction_id: Option<Uuid>,
+    // The error body the transaction request failed with.
+    pub error: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateScheduledTransactionRequest {
+    #[serde(rename = "type")]
+    pub tx_type: TransactionType,
+    pub amount: i64,
+    pub source_account_id: Option<Uuid>,
+    pub dest_account_id: Option<Uuid>,
+    // First (or only) run.
+    pub start_at: DateTime<Utc>,
+    // Omit for a one-off schedule.
+    pub frequency: Option<ScheduleFrequency>,
+    // Last time a recurring schedule may run (inclusive).
+    pub end_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListScheduledTransactionsQuery {
+    pub status: Option<ScheduleStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledTransactionResponse {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub tx_type: TransactionType,
+    pub source_account_id: Option<Uuid>,
+    pub dest_account_id: Option<Uuid>,
+    pub amount: i64,
+    pub frequency: Option<ScheduleFrequency>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: Option<DateTime<Utc>>,
+    pub status: ScheduleStatus,
+    pub run_count: i32,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ScheduledTransaction> for ScheduledTransactionResponse {
+    fn from(s: ScheduledTransaction) -> Self {
+        Self {
+            id: s.id,
+            tx_type: s.tx_type,
+            source_account_id: s.source_account_id,
+            dest_account_id: s.dest_account_id,
+            amount: s.amount,
+            frequency: s.frequency,
+            start_at: s.start_at,
+            end_at: s.end_at,
+            status: s.status,
+            run_count: s.run_count,
+            next_run_at: s.next_run_at,
+            last_run_at: s.last_run_at,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledTransactionRunResponse {
+    pub id: Uuid,
+    pub occurrence: i32,
+    pub scheduled_for: DateTime<Utc>,
+    pub status: ScheduledRunStatus,
+    pub transaction_id: Option<Uuid>,
+    pub error: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ScheduledTransactionRun> for ScheduledTransactionRunResponse {
+    fn from(r: ScheduledTransactionRun) -> Self {
+        Self {
+            id: r.id,
+            occurrence: r.occurrence,
+            scheduled_for: r.scheduled_for,
+            status: r.status,
+            transaction_id: r.transaction_id,
+            error: r.error,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn daily_and_weekly_occurrences() {
+        let start = at("2026-01-30T09:00:00Z");
+        let daily = ScheduleFrequency::Daily;
+        assert_eq!(daily.occurrence(start, 0), Some(start));
+        assert_eq!(daily.occurrence(start, 3), Some(at("2026-02-02T09:00:00Z")));
+        let weekly = ScheduleFrequency::Weekly;
+        assert_eq!(weekly.occurrence(start, 5), Some(at("2026-03-06T09:00:00Z")));
+    }
+
+    #[test]
+    fn monthly_occurrences_clamp_to_month_end_and_return_to_the_31st() {
+        let monthly = ScheduleFrequency::Monthly;
+
+        let start = at("2026-01-31T09:00:00Z");
+        let runs: Vec<_> = (0..4).map(|n| monthly.occurrence(start, n).unwrap()).collect();
+        assert_eq!(
+            runs,
+            vec![
+                at("2026-01-31T09:00:00Z"),
+                at("2026-02-28T09:00:00Z"),
+                at("2026-03-31T09:00:00Z"),
+                at("2026-04-30T09:00:00Z"),
+            ]
+        );
+
+        // Leap year.
+        let start = at("2028-01-31T09:00:00Z");
+        assert_eq!(monthly.occurrence(start, 1), Some(at("2028-02-29T09:00:00Z")));
+        assert_eq!(monthly.occurrence(start, 2), Some(at("2028-03-31T09:00:00Z")));
+
+        // Across the year boundary.
+        let start = at("2026-11-30T00:00:00Z");
+        assert_eq!(monthly.occurrence(start, 3), Some(at("2027-02-28T00:00:00Z")));
+        assert_eq!(monthly.occurrence(start, 4), Some(at("2027-03-30T00:00:00Z")));
+    }
+}
@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use crate::currency;
+use crate::metadata::Metadata;
+use crate::models::fx::{FxConversion, FxConversionResponse};
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TransactionType {
@@ -19,6 +23,15 @@ impl TransactionType {
             TransactionType::Transfer => "transfer",
         }
     }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "credit" => Some(TransactionType::Credit),
+            "debit" => Some(TransactionType::Debit),
+            "transfer" => Some(TransactionType::Transfer),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -29,16 +42,159 @@ pub struct Transaction {
     pub source_account_id: Option<Uuid>,
     pub dest_account_id: Option<Uuid>,
     pub amount: i64,
+    pub currency: String,
+    // Set for cross-currency transfers only.
+    pub fx: Option<FxConversion>,
+    // Set on compensating transactions created by POST /api/transactions/{id}/reverse.
+    pub reversal_of: Option<Uuid>,
+    // Total reversed so far, in `amount` units.
+    pub reversed_amount: i64,
+    pub metadata: Metadata,
+    pub external_reference: Option<String>,
+    pub description: Option<String>,
+    // The external side of a credit or debit; never set on transfers.
+    pub counterparty: Option<Counterparty>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+// Who sent the money of a credit, or received the money of a debit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Counterparty {
+    pub name: String,
+    // Their account at the other institution, e.g. an IBAN or a masked card number.
+    #[serde(default)]
+    pub account_identifier: Option<String>,
+    // Their reference for the payment, e.g. a bank transfer reference.
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+// Serialized to fingerprint Idempotency-Key requests; new optional fields are skipped when
+// absent so fingerprints of existing keys stay stable.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateTransactionRequest {
     #[serde(rename = "type")]
     pub tx_type: TransactionType,
     pub amount: i64,
     pub source_account_id: Option<Uuid>,
     pub dest_account_id: Option<Uuid>,
+    // Cross-currency transfers: dest currency per source currency unit, e.g. "0.92".
+    // Defaults to the business's rate table when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx_rate: Option<String>,
+    // Executes a transfer at the rate and amounts locked by POST /api/fx/quotes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_reference: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // Credits and debits only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<Counterparty>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    // Commit every item or none.
+    #[default]
+    AllOrNothing,
+    // Commit each item that succeeds on its own; failed items are skipped.
+    BestEffort,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTransactionBatchRequest {
+    #[serde(default)]
+    pub mode: BatchMode,
+    pub transactions: Vec<CreateTransactionRequest>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Succeeded,
+    Failed,
+    // Would have succeeded, but another item failed in all_or_nothing mode.
+    RolledBack,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: BatchItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionResponse>,
+    // Same `code`/`error`/`details` as a single request's error body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub mode: BatchMode,
+    // Whether anything was committed.
+    pub committed: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReverseTransactionRequest {
+    // In the original transaction's `amount` units; defaults to everything not yet reversed.
+    pub amount: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTransactionsQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    #[serde(rename = "type")]
+    pub tx_type: Option<TransactionType>,
+    pub account_id: Option<Uuid>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub external_reference: Option<String>,
+    // JSON object, e.g. {"order_id":"A-1001"}.
+    pub metadata: Option<String>,
+}
+
+// Validated filters for db::list_transactions; every field is optional.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub tx_type: Option<TransactionType>,
+    // Matches either source_account_id or dest_account_id.
+    pub account_id: Option<Uuid>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    // Inclusive lower bound.
+    pub created_from: Option<DateTime<Utc>>,
+    // Exclusive upper bound.
+    pub created_to: Option<DateTime<Utc>>,
+    pub external_reference: Option<String>,
+    // Top-level keys that must have exactly these values.
+    pub metadata: Option<Metadata>,
+    // Account-restricted API keys: only transactions touching one of these accounts.
+    pub allowed_account_ids: Option<Vec<Uuid>>,
+}
+
+impl Transaction {
+    pub fn touches_any(&self, account_ids: &[Uuid]) -> bool {
+        [self.source_account_id, self.dest_account_id]
+            .into_iter()
+            .flatten()
+            .any(|id| account_ids.contains(&id))
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -49,17 +205,39 @@ pub struct TransactionResponse {
     pub source_account_id: Option<Uuid>,
     pub dest_account_id: Option<Uuid>,
     pub amount: i64,
+    pub currency: String,
+    // `amount` as a decimal string using the currency exponent, e.g. "10.50".
+    pub amount_decimal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fx: Option<FxConversionResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reversal_of: Option<Uuid>,
+    pub reversed_amount: i64,
+    pub metadata: Metadata,
+    pub external_reference: Option<String>,
+    pub description: Option<String>,
+    pub counterparty: Option<Counterparty>,
     pub created_at: DateTime<Utc>,
 }
 
 impl From<Transaction> for TransactionResponse {
     fn from(t: Transaction) -> Self {
+        let currency = t.currency.trim().to_string();
         Self {
             id: t.id,
             tx_type: t.tx_type,
             source_account_id: t.source_account_id,
             dest_account_id: t.dest_account_id,
             amount: t.amount,
+            amount_decimal: currency::format_amount(t.amount, &currency),
+            currency,
+            fx: t.fx.map(FxConversionResponse::from),
+            reversal_of: t.reversal_of,
+            reversed_amount: t.reversed_amount,
+            metadata: t.metadata,
+            external_reference: t.external_reference,
+            description: t.description,
+            counterparty: t.counterparty,
             created_at: t.created_at,
         }
     }
@@ -12,6 +12,7 @@ pub struct WebhookEndpoint {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateWebhookEndpointRequest {
     pub url: String,
 }
@@ -22,6 +23,9 @@ pub struct WebhookEndpointResponse {
     pub url: String,
     pub active: bool,
     pub created_at: DateTime<Utc>,
+    // Signing secret; only returned once, when the endpoint is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
 }
 
 impl From<WebhookEndpoint> for WebhookEndpointResponse {
@@ -31,6 +35,7 @@ impl From<WebhookEndpoint> for WebhookEndpointResponse {
             url: e.url,
             active: e.active,
             created_at: e.created_at,
+            secret: None,
         }
     }
 }
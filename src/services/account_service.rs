@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::models::account::{Account, AccountLimits, AccountStatus, DailyBalance};
+use crate::services::webhook_service::enqueue_account_status_changed_events;
+
+#[derive(Debug)]
+pub enum AccountError {
+    AccountNotFound,
+    // The account's current status does not allow the change.
+    InvalidStatus(&'static str),
+    Internal,
+}
+
+// Allowed transitions: active <-> frozen, and active or frozen -> closed (final).
+fn check_transition(account: &Account, to: AccountStatus) -> Result<(), AccountError> {
+    match (account.status, to) {
+        (AccountStatus::Closed, _) => Err(AccountError::InvalidStatus("account is closed")),
+        (AccountStatus::Active, AccountStatus::Active) => {
+            Err(AccountError::InvalidStatus("account is not frozen"))
+        }
+        (AccountStatus::Frozen, AccountStatus::Frozen) => {
+            Err(AccountError::InvalidStatus("account is already frozen"))
+        }
+        (_, AccountStatus::Closed) if account.balance != 0 || account.available_balance != 0 => {
+            Err(AccountError::InvalidStatus(
+                "account must have a zero balance and no active holds to be closed",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Moves the account to `to` (freeze, unfreeze or close) under the account row lock and
+/// enqueues an `account.status_changed` webhook event in the same DB transaction.
+pub async fn change_account_status(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    account_id: Uuid,
+    to: AccountStatus,
+) -> Result<Account, AccountError> {
+    if allowed_account_ids.is_some_and(|ids| !ids.contains(&account_id)) {
+        return Err(AccountError::AccountNotFound);
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| AccountError::Internal)?;
+
+    let account = db::lock_account_for_change(&mut *tx, business_id, account_id)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::AccountNotFound)?;
+
+    check_transition(&account, to)?;
+
+    let updated = db::set_account_status(&mut *tx, account_id, to)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::Internal)?;
+
+    enqueue_account_status_changed_events(&mut tx, business_id, &updated, account.status)
+        .await
+        .map_err(|_| AccountError::Internal)?;
+
+    tx.commit().await.map_err(|_| AccountError::Internal)?;
+
+    Ok(updated)
+}
+
+/// Sets the account's overdraft limit under the account row lock and records the change with
+/// the API key that made it. Setting the current limit again changes nothing and is not recorded.
+pub async fn set_overdraft_limit(
+    pool: &PgPool,
+    business_id: Uuid,
+    api_key_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    account_id: Uuid,
+    overdraft_limit: i64,
+) -> Result<Account, AccountError> {
+    if allowed_account_ids.is_some_and(|ids| !ids.contains(&account_id)) {
+        return Err(AccountError::AccountNotFound);
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| AccountError::Internal)?;
+
+    let account = db::lock_account_for_change(&mut *tx, business_id, account_id)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::AccountNotFound)?;
+
+    if account.status == AccountStatus::Closed {
+        return Err(AccountError::InvalidStatus("account is closed"));
+    }
+    if account.overdraft_limit == overdraft_limit {
+        return Ok(account);
+    }
+    // Lowering the limit must leave the balance (net of holds) within it.
+    if account.available_balance < -overdraft_limit {
+        return Err(AccountError::InvalidStatus(
+            "account balance net of holds is below the new overdraft limit",
+        ));
+    }
+
+    let updated = db::set_account_overdraft_limit(&mut *tx, account_id, overdraft_limit)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::Internal)?;
+
+    db::insert_overdraft_limit_change(
+        &mut *tx,
+        business_id,
+        account_id,
+        account.overdraft_limit,
+        overdraft_limit,
+        api_key_id,
+    )
+    .await
+    .map_err(|_| AccountError::Internal)?;
+
+    tx.commit().await.map_err(|_| AccountError::Internal)?;
+
+    Ok(updated)
+}
+
+/// Replaces the account's risk limits under the account row lock. The new limits apply to
+/// transactions that lock the account afterwards; nothing already recorded is re-checked.
+pub async fn set_account_limits(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    account_id: Uuid,
+    limits: &AccountLimits,
+) -> Result<Account, AccountError> {
+    if allowed_account_ids.is_some_and(|ids| !ids.contains(&account_id)) {
+        return Err(AccountError::AccountNotFound);
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| AccountError::Internal)?;
+
+    let account = db::lock_account_for_change(&mut *tx, business_id, account_id)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::AccountNotFound)?;
+
+    if account.status == AccountStatus::Closed {
+        return Err(AccountError::InvalidStatus("account is closed"));
+    }
+
+    let updated = db::set_account_limits(&mut *tx, account_id, limits)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .ok_or(AccountError::Internal)?;
+
+    tx.commit().await.map_err(|_| AccountError::Internal)?;
+
+    Ok(updated)
+}
+
+/// End-of-day balances of every day from `from` through `to`. Days with a snapshot use it; the
+/// others (not reached by the snapshot worker yet, e.g. today so far) are rolled forward from
+/// the previous day with that day's postings.
+pub async fn end_of_day_balances(
+    pool: &PgPool,
+    account_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DailyBalance>, AccountError> {
+    let snapshots: HashMap<NaiveDate, i64> = db::list_balance_snapshots(pool, account_id, from, to)
+        .await
+        .map_err(|_| AccountError::Internal)?
+        .into_iter()
+        .collect();
+
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|d| *d <= to).collect();
+
+    // Balance at the start of the first day without a snapshot, and the postings from there on.
+    let (opening, totals) = match days.iter().find(|d| !snapshots.contains_key(d)) {
+        None => (0, HashMap::new()),
+        Some(&first_missing) => {
+            let opening_at = first_missing.and_time(NaiveTime::MIN).and_utc();
+            let opening = db::account_balance_at(pool, account_id, opening_at)
+                .await
+                .map_err(|_| AccountError::Internal)?;
+            let totals: HashMap<NaiveDate, i64> =
+                db::daily_posting_totals(pool, account_id, first_missing, to)
+                    .await
+                    .map_err(|_| AccountError::Internal)?
+                    .into_iter()
+                    .collect();
+            (opening, totals)
+        }
+    };
+
+    Ok(merge_daily_balances(days, &snapshots, opening, &totals))
+}
+
+// End-of-day balance of each of `days`: its snapshot if there is one, otherwise the previous
+// day's balance plus the day's posting total. `opening` is the balance at the start of the
+// first day without a snapshot.
+fn merge_daily_balances(
+    days: Vec<NaiveDate>,
+    snapshots: &HashMap<NaiveDate, i64>,
+    opening: i64,
+    totals: &HashMap<NaiveDate, i64>,
+) -> Vec<DailyBalance> {
+    let mut balance = opening;
+    days.into_iter()
+        .map(|date| {
+            balance = match snapshots.get(&date) {
+                Some(snapshot) => *snapshot,
+                None => balance + totals.get(&date).copied().unwrap_or(0),
+            };
+            DailyBalance { date, balance }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Datelike;
+
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn merge(
+        days: std::ops::RangeInclusive<u32>,
+        snapshots: &[(u32, i64)],
+        opening: i64,
+        totals: &[(u32, i64)],
+    ) -> Vec<(u32, i64)> {
+        let by_day = |rows: &[(u32, i64)]| rows.iter().map(|&(d, v)| (day(d), v)).collect();
+        merge_daily_balances(days.map(day).collect(), &by_day(snapshots), opening, &by_day(totals))
+            .into_iter()
+            .map(|b| (b.date.day(), b.balance))
+            .collect()
+    }
+
+    #[test]
+    fn uses_snapshots_when_every_day_has_one() {
+        let balances = merge(1..=3, &[(1, 100), (2, 250), (3, 90)], 0, &[]);
+        assert_eq!(balances, vec![(1, 100), (2, 250), (3, 90)]);
+    }
+
+    #[test]
+    fn rolls_today_forward_from_the_last_snapshot() {
+        // Day 3 is today: the snapshot worker has not reached it yet.
+        let balances = merge(1..=3, &[(1, 100), (2, 250)], 250, &[(3, -40)]);
+        assert_eq!(balances, vec![(1, 100), (2, 250), (3, 210)]);
+    }
+
+    #[test]
+    fn rolls_forward_across_gaps_and_resumes_at_the_next_snapshot() {
+        // No snapshots for days 2-3 (e.g. the worker was down); day 4 has one again.
+        let balances = merge(
+            1..=5,
+            &[(1, 100), (4, 500)],
+            100,
+            &[(2, 30), (4, 999), (5, -20)],
+        );
+        assert_eq!(balances, vec![(1, 100), (2, 130), (3, 130), (4, 500), (5, 480)]);
+    }
+
+    #[test]
+    fn starts_from_the_opening_balance_without_snapshots() {
+        let balances = merge(1..=3, &[], 1000, &[(1, -100), (3, 50)]);
+        assert_eq!(balances, vec![(1, 900), (2, 900), (3, 950)]);
+    }
+}
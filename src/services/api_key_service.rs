@@ -0,0 +1,183 @@
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::middleware::auth::{BusinessContext, hmac_secret_fingerprint, hmac_sha256_hex};
+use crate::models::api_key::{ApiKey, Scope};
+
+pub const DEFAULT_ROTATION_OVERLAP_SECS: i64 = 24 * 60 * 60;
+pub const MAX_ROTATION_OVERLAP_SECS: i64 = 7 * 24 * 60 * 60;
+
+const KEY_PREFIX_LEN: usize = 16;
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
+    Forbidden(&'static str),
+    Internal,
+}
+
+#[derive(Debug)]
+pub struct GeneratedApiKey {
+    pub raw: String,
+    pub prefix: String,
+    pub hash: String,
+    pub hmac_secret_fingerprint: String,
+}
+
+// Raw key is "dk_live_" + 48 hex chars; only the HMAC and a short prefix are stored.
+pub fn generate_api_key(hmac_secret: &str) -> GeneratedApiKey {
+    let mut bytes = [0u8; 24];
+    rand::rng().fill(&mut bytes);
+
+    let raw = format!("dk_live_{}", hex::encode(bytes));
+    let prefix = raw[..KEY_PREFIX_LEN].to_string();
+    let hash = hmac_sha256_hex(hmac_secret, &raw);
+
+    GeneratedApiKey {
+        raw,
+        prefix,
+        hash,
+        hmac_secret_fingerprint: hmac_secret_fingerprint(hmac_secret),
+    }
+}
+
+// Returns the normalized scope list. A key can only mint keys with a subset of its own scopes.
+pub fn validate_requested_scopes(
+    ctx: &BusinessContext,
+    requested: &[String],
+) -> Result<Vec<String>, ApiKeyError> {
+    if requested.is_empty() {
+        return Err(ApiKeyError::Validation {
+            field: "scopes",
+            message: "scopes must not be empty",
+        });
+    }
+
+    let mut scopes: Vec<Scope> = Vec::with_capacity(requested.len());
+    for raw in requested {
+        let scope = Scope::parse(raw.trim()).ok_or(ApiKeyError::Validation {
+            field: "scopes",
+            message: "unknown scope; expected one of accounts:read, accounts:write, \
+                      transactions:read, transactions:write, webhooks:manage, api_keys:manage, \
+                      fx:manage",
+        })?;
+        if !ctx.has_scope(scope) {
+            return Err(ApiKeyError::Forbidden(
+                "cannot grant a scope the calling key does not have",
+            ));
+        }
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    Ok(scopes.into_iter().map(|s| s.as_str().to_string()).collect())
+}
+
+// A restricted key can only mint keys restricted to a subset of its own accounts.
+// Account existence is checked separately against the DB.
+pub fn validate_requested_accounts(
+    ctx: &BusinessContext,
+    requested: Option<&[Uuid]>,
+) -> Result<Option<Vec<Uuid>>, ApiKeyError> {
+    let Some(requested) = requested else {
+        return match &ctx.allowed_account_ids {
+            Some(_) => Err(ApiKeyError::Forbidden(
+                "an account-restricted key can only create account-restricted keys",
+            )),
+            None => Ok(None),
+        };
+    };
+
+    if requested.is_empty() {
+        return Err(ApiKeyError::Validation {
+            field: "account_ids",
+            message: "account_ids must not be empty when provided",
+        });
+    }
+
+    let mut ids: Vec<Uuid> = Vec::with_capacity(requested.len());
+    for id in requested {
+        if !ctx.can_access_account(*id) {
+            return Err(ApiKeyError::Forbidden(
+                "cannot grant an account the calling key does not have",
+            ));
+        }
+        if !ids.contains(id) {
+            ids.push(*id);
+        }
+    }
+
+    Ok(Some(ids))
+}
+
+// A key can only list, rotate or revoke keys that it could have created itself: rotating hands
+// out a new secret with the target's permissions, so a broader target would be an escalation.
+pub fn ensure_manageable(ctx: &BusinessContext, key: &ApiKey) -> Result<(), ApiKeyError> {
+    validate_requested_scopes(ctx, &key.scopes).map_err(|_| {
+        ApiKeyError::Forbidden("cannot manage a key with scopes the calling key does not have")
+    })?;
+    validate_requested_accounts(ctx, key.allowed_account_ids.as_deref()).map_err(|_| {
+        ApiKeyError::Forbidden("cannot manage a key with accounts the calling key does not have")
+    })?;
+    Ok(())
+}
+
+pub fn validate_rotation_overlap(overlap_seconds: Option<i64>) -> Result<i64, ApiKeyError> {
+    let overlap = overlap_seconds.unwrap_or(DEFAULT_ROTATION_OVERLAP_SECS);
+    if !(0..=MAX_ROTATION_OVERLAP_SECS).contains(&overlap) {
+        return Err(ApiKeyError::Validation {
+            field: "overlap_seconds",
+            message: "overlap_seconds must be between 0 and 604800",
+        });
+    }
+    Ok(overlap)
+}
+
+#[derive(Debug, Default)]
+pub struct KeySecretReport {
+    pub primary: i64,
+    // One count per entry of previous_hmac_secrets, in config order.
+    pub previous: Vec<i64>,
+    // Not used since fingerprints were introduced, or hashed with a secret no longer configured.
+    pub unknown: i64,
+}
+
+/// Counts active API keys by the HMAC secret their stored hash was derived with.
+///
+/// Keys move to the primary secret the next time they authenticate, so once `previous`
+/// and `unknown` reach zero the old secrets can be dropped from the config.
+pub async fn report_key_secrets(
+    pool: &PgPool,
+    hmac_secret: &str,
+    previous_hmac_secrets: &[String],
+) -> Result<KeySecretReport, sqlx::Error> {
+    let primary_fp = hmac_secret_fingerprint(hmac_secret);
+    let previous_fps: Vec<String> = previous_hmac_secrets
+        .iter()
+        .map(|s| hmac_secret_fingerprint(s))
+        .collect();
+
+    let mut report = KeySecretReport {
+        previous: vec![0; previous_fps.len()],
+        ..Default::default()
+    };
+
+    for (fingerprint, count) in db::count_active_api_keys_by_secret(pool).await? {
+        match fingerprint {
+            Some(fp) if fp == primary_fp => report.primary += count,
+            Some(fp) => match previous_fps.iter().position(|p| *p == fp) {
+                Some(i) => report.previous[i] += count,
+                None => report.unknown += count,
+            },
+            None => report.unknown += count,
+        }
+    }
+
+    Ok(report)
+}
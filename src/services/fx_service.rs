@@ -0,0 +1,340 @@
+use std::fmt;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::currency;
+use crate::db;
+use crate::models::fx::{CreateFxQuoteRequest, FxConversion, FxQuote};
+
+#[derive(Debug)]
+pub enum FxError {
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
+    AccountNotFound,
+    // No usable rate between the two currencies.
+    CurrencyMismatch,
+    Internal,
+}
+
+/// How long a quote's rate stays locked.
+pub const QUOTE_TTL_SECS: i64 = 60;
+
+const MAX_RATE_INTEGER_DIGITS: usize = 12;
+const MAX_RATE_FRACTION_DIGITS: usize = 12;
+
+/// Exchange rate as an exact decimal: `units / 10^scale` units of the quote (destination)
+/// currency per one unit of the base (source) currency, in major units.
+///
+/// Matches the `NUMERIC(24, 12)` columns it is stored in; floats are never involved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxRate {
+    units: i128,
+    scale: u32,
+}
+
+impl FxRate {
+    pub fn parse(input: &str) -> Option<Self> {
+        let s = input.trim();
+        let (int_part, frac_part) = match s.split_once('.') {
+            Some((i, f)) => (i, f),
+            None => (s, ""),
+        };
+
+        let digits_ok = |p: &str| p.chars().all(|c| c.is_ascii_digit());
+        if int_part.is_empty()
+            || int_part.len() > MAX_RATE_INTEGER_DIGITS
+            || frac_part.len() > MAX_RATE_FRACTION_DIGITS
+            || (s.contains('.') && frac_part.is_empty())
+            || !digits_ok(int_part)
+            || !digits_ok(frac_part)
+        {
+            return None;
+        }
+
+        let units: i128 = format!("{int_part}{frac_part}").parse().ok()?;
+        if units == 0 {
+            return None;
+        }
+
+        Some(Self {
+            units,
+            scale: frac_part.len() as u32,
+        })
+    }
+
+    /// The opposite direction (`1 / rate`), rounded half up to the maximum fractional digits.
+    ///
+    /// Returns None if the result does not fit the stored precision.
+    pub fn inverse(self) -> Option<Self> {
+        let scale = MAX_RATE_FRACTION_DIGITS as u32;
+        let numerator = 10i128.checked_pow(self.scale + scale)?;
+
+        let mut units = numerator / self.units;
+        if (numerator % self.units) * 2 >= self.units {
+            units += 1;
+        }
+
+        if units == 0 || units >= 10i128.pow(scale + MAX_RATE_INTEGER_DIGITS as u32) {
+            return None;
+        }
+
+        Some(Self { units, scale })
+    }
+}
+
+// Canonical form without trailing zeros ("0.920000" -> "0.92"), as returned by the API.
+impl fmt::Display for FxRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10i128.pow(self.scale);
+        let int_part = self.units / scale;
+        let frac = format!("{:0width$}", self.units % scale, width = self.scale as usize);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            write!(f, "{int_part}")
+        } else {
+            write!(f, "{int_part}.{frac}")
+        }
+    }
+}
+
+/// Converts `amount` minor units of a currency with exponent `from_exponent` into minor units
+/// of a currency with exponent `to_exponent`, rounding half up to the destination minor unit.
+///
+/// Returns None on overflow.
+pub fn convert_minor_units(
+    amount: i64,
+    rate: FxRate,
+    from_exponent: u32,
+    to_exponent: u32,
+) -> Option<i64> {
+    if amount < 0 {
+        return None;
+    }
+
+    // dest = amount / 10^from * rate * 10^to = amount * units * 10^to / (10^scale * 10^from)
+    let numerator = (amount as i128)
+        .checked_mul(rate.units)?
+        .checked_mul(10i128.checked_pow(to_exponent)?)?;
+    let denominator = 10i128
+        .checked_pow(rate.scale)?
+        .checked_mul(10i128.checked_pow(from_exponent)?)?;
+
+    let mut quotient = numerator / denominator;
+    if (numerator % denominator) * 2 >= denominator {
+        quotient += 1;
+    }
+
+    i64::try_from(quotient).ok()
+}
+
+/// Converts `amount` (minor units of `source_currency`) at `rate` into `dest_currency`.
+pub fn conversion_for(
+    amount: i64,
+    source_currency: &str,
+    dest_currency: &str,
+    rate: FxRate,
+) -> Result<FxConversion, FxError> {
+    // Accounts created before currency validation may use codes without a known exponent.
+    let (Some(from), Some(to)) = (currency::find(source_currency), currency::find(dest_currency))
+    else {
+        return Err(FxError::CurrencyMismatch);
+    };
+
+    let dest_amount = convert_minor_units(amount, rate, from.exponent, to.exponent).ok_or(
+        FxError::Validation {
+            field: "amount",
+            message: "amount is too large to convert",
+        },
+    )?;
+    if dest_amount == 0 {
+        return Err(FxError::Validation {
+            field: "amount",
+            message: "amount converts to 0 in the destination currency",
+        });
+    }
+
+    Ok(FxConversion {
+        rate: rate.to_string(),
+        dest_amount,
+        dest_currency: to.code.to_string(),
+    })
+}
+
+/// Validates a `{base}/{quote}` pair from the rates API and returns the normalized codes.
+pub fn validate_currency_pair(base: &str, quote: &str) -> Result<(String, String), FxError> {
+    let base = currency::find(&base.trim().to_ascii_uppercase()).ok_or(FxError::Validation {
+        field: "base_currency",
+        message: "base_currency must be an ISO 4217 code, e.g. USD",
+    })?;
+    let quote = currency::find(&quote.trim().to_ascii_uppercase()).ok_or(FxError::Validation {
+        field: "quote_currency",
+        message: "quote_currency must be an ISO 4217 code, e.g. EUR",
+    })?;
+
+    if base == quote {
+        return Err(FxError::Validation {
+            field: "quote_currency",
+            message: "base_currency and quote_currency must differ",
+        });
+    }
+
+    Ok((base.code.to_string(), quote.code.to_string()))
+}
+
+pub fn validate_rate(rate: &str) -> Result<FxRate, FxError> {
+    FxRate::parse(rate).ok_or(FxError::Validation {
+        field: "rate",
+        message: "rate must be a positive decimal string with at most 12 integer and 12 \
+                  fractional digits, e.g. \"0.9215\"",
+    })
+}
+
+/// Locks the business's current table rate for `amount` between two of its accounts.
+///
+/// `allowed_account_ids` is the caller's API key allowlist (None = every account).
+pub async fn create_quote(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    req: &CreateFxQuoteRequest,
+) -> Result<FxQuote, FxError> {
+    if req.amount <= 0 {
+        return Err(FxError::Validation {
+            field: "amount",
+            message: "amount must be > 0",
+        });
+    }
+    if req.source_account_id == req.dest_account_id {
+        return Err(FxError::Validation {
+            field: "dest_account_id",
+            message: "quote requires distinct source and destination accounts",
+        });
+    }
+
+    if let Some(allowed) = allowed_account_ids {
+        let out_of_scope = [req.source_account_id, req.dest_account_id]
+            .iter()
+            .any(|id| !allowed.contains(id));
+        if out_of_scope {
+            return Err(FxError::AccountNotFound);
+        }
+    }
+
+    let source = db::get_account(pool, business_id, req.source_account_id)
+        .await
+        .map_err(|_| FxError::Internal)?
+        .ok_or(FxError::AccountNotFound)?;
+    let dest = db::get_account(pool, business_id, req.dest_account_id)
+        .await
+        .map_err(|_| FxError::Internal)?
+        .ok_or(FxError::AccountNotFound)?;
+
+    let (source_currency, dest_currency) = (source.currency.trim(), dest.currency.trim());
+    if source_currency == dest_currency {
+        return Err(FxError::Validation {
+            field: "dest_account_id",
+            message: "quote requires accounts with different currencies",
+        });
+    }
+
+    let stored = db::find_fx_rate(pool, business_id, source_currency, dest_currency)
+        .await
+        .map_err(|_| FxError::Internal)?;
+    let rate = stored
+        .as_deref()
+        .and_then(FxRate::parse)
+        .ok_or(FxError::CurrencyMismatch)?;
+
+    let conversion = conversion_for(req.amount, source_currency, dest_currency, rate)?;
+
+    let quote = db::NewFxQuote {
+        source_account_id: source.id,
+        dest_account_id: dest.id,
+        amount: req.amount,
+        currency: source_currency,
+        conversion: &conversion,
+        ttl_secs: QUOTE_TTL_SECS,
+    };
+
+    db::create_fx_quote(pool, business_id, &quote)
+        .await
+        .map_err(|_| FxError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(s: &str) -> FxRate {
+        FxRate::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_accepts_decimal_rates() {
+        assert_eq!(rate("0.92").to_string(), "0.92");
+        assert_eq!(rate(" 0.920000 ").to_string(), "0.92");
+        assert_eq!(rate("150").to_string(), "150");
+        assert_eq!(rate("0.000000000001").to_string(), "0.000000000001");
+        assert_eq!(rate("999999999999.5").to_string(), "999999999999.5");
+    }
+
+    #[test]
+    fn parse_rejects_zero_negative_and_malformed_rates() {
+        for input in [
+            "", "0", "0.0", "-1", "-0.5", "+1", "1.", ".5", "1.2.3", "abc", "1e3", "1,5",
+            "0x10", "1 000", "0.0000000000001", "1000000000000",
+        ] {
+            assert_eq!(FxRate::parse(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn inverse_rounds_half_up_to_twelve_digits() {
+        assert_eq!(rate("0.5").inverse().unwrap().to_string(), "2");
+        assert_eq!(rate("3").inverse().unwrap().to_string(), "0.333333333333");
+        assert_eq!(rate("1.5").inverse().unwrap().to_string(), "0.666666666667");
+        assert_eq!(rate("8").inverse().unwrap().to_string(), "0.125");
+    }
+
+    #[test]
+    fn inverse_rejects_results_outside_the_stored_precision() {
+        // 1 / 10^-12 = 10^12 needs 13 integer digits.
+        assert_eq!(rate("0.000000000001").inverse(), None);
+        // The largest rates still have a non-zero inverse.
+        assert_eq!(rate("999999999999").inverse().unwrap().to_string(), "0.000000000001");
+    }
+
+    #[test]
+    fn converts_between_currency_exponents() {
+        // 10.00 USD at 150.5 JPY/USD.
+        assert_eq!(convert_minor_units(1000, rate("150.5"), 2, 0), Some(1505));
+        // 1505 JPY at 0.0066 USD/JPY = 9.933 USD.
+        assert_eq!(convert_minor_units(1505, rate("0.0066"), 0, 2), Some(993));
+        // 1.000 KWD (3 decimals) at 3.25 USD/KWD.
+        assert_eq!(convert_minor_units(1000, rate("3.25"), 3, 2), Some(325));
+        assert_eq!(convert_minor_units(0, rate("1.1"), 2, 2), Some(0));
+    }
+
+    #[test]
+    fn conversion_rounds_half_up() {
+        assert_eq!(convert_minor_units(5, rate("0.5"), 2, 2), Some(3));
+        assert_eq!(convert_minor_units(3, rate("0.5"), 2, 2), Some(2));
+        assert_eq!(convert_minor_units(1, rate("0.49"), 2, 2), Some(0));
+        assert_eq!(convert_minor_units(1, rate("0.5"), 2, 2), Some(1));
+        // Converting to a currency without minor units rounds to whole units.
+        assert_eq!(convert_minor_units(150, rate("1"), 2, 0), Some(2));
+        assert_eq!(convert_minor_units(149, rate("1"), 2, 0), Some(1));
+    }
+
+    #[test]
+    fn conversion_overflow_returns_none() {
+        assert_eq!(convert_minor_units(i64::MAX, rate("2"), 2, 2), None);
+        assert_eq!(convert_minor_units(i64::MAX, rate("999999999999"), 0, 3), None);
+        assert_eq!(convert_minor_units(1, rate("1"), 2, 40), None);
+        assert_eq!(convert_minor_units(-1, rate("1"), 2, 2), None);
+        assert_eq!(convert_minor_units(i64::MAX, rate("1"), 2, 2), Some(i64::MAX));
+    }
+}
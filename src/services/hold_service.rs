@@ -0,0 +1,306 @@
+use sqlx::{PgPool, Postgres, Transaction as SqlxTransaction};
+use uuid::Uuid;
+
+use crate::db;
+use crate::models::account::AccountLimit;
+use crate::models::hold::{CaptureHoldRequest, CreateHoldRequest, Hold, HoldStatus};
+use crate::models::transaction::{Transaction, TransactionType};
+use crate::services::transaction_service::{
+    NewTransaction, TransactionError, check_outflow_limits, convert_for_transfer,
+    lock_account_for_update, lock_transfer_accounts, record_transaction,
+};
+use crate::services::webhook_service::enqueue_transaction_created_events;
+
+#[derive(Debug)]
+pub enum HoldError {
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
+    AccountNotFound,
+    AccountFrozen,
+    AccountClosed,
+    HoldNotFound,
+    InsufficientFunds,
+    CurrencyMismatch,
+    LimitExceeded(AccountLimit),
+    // Already captured, voided or expired.
+    NotActive(&'static str),
+    Internal,
+}
+
+impl From<TransactionError> for HoldError {
+    fn from(err: TransactionError) -> Self {
+        match err {
+            TransactionError::Validation { field, message } => {
+                HoldError::Validation { field, message }
+            }
+            TransactionError::AccountNotFound => HoldError::AccountNotFound,
+            TransactionError::AccountFrozen => HoldError::AccountFrozen,
+            TransactionError::AccountClosed => HoldError::AccountClosed,
+            TransactionError::InsufficientFunds => HoldError::InsufficientFunds,
+            TransactionError::CurrencyMismatch => HoldError::CurrencyMismatch,
+            TransactionError::LimitExceeded(limit) => HoldError::LimitExceeded(limit),
+            _ => HoldError::Internal,
+        }
+    }
+}
+
+pub const DEFAULT_HOLD_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+pub const MAX_HOLD_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+
+fn ensure_active(hold: &Hold, expired: bool) -> Result<(), HoldError> {
+    match hold.status {
+        HoldStatus::Active if expired => Err(HoldError::NotActive("hold has expired")),
+        HoldStatus::Active => Ok(()),
+        HoldStatus::Captured => Err(HoldError::NotActive("hold has already been captured")),
+        HoldStatus::Voided => Err(HoldError::NotActive("hold has been voided")),
+        HoldStatus::Expired => Err(HoldError::NotActive("hold has expired")),
+    }
+}
+
+fn is_allowed(allowed_account_ids: Option<&[Uuid]>, account_id: Uuid) -> bool {
+    allowed_account_ids.is_none_or(|ids| ids.contains(&account_id))
+}
+
+/// Reserves `amount` of the account's available balance until the hold is captured, voided
+/// or expires. No money moves and no transaction is created.
+pub async fn place_hold(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    req: &CreateHoldRequest,
+) -> Result<Hold, HoldError> {
+    if req.amount <= 0 {
+        return Err(HoldError::Validation {
+            field: "amount",
+            message: "amount must be > 0",
+        });
+    }
+    let ttl = req.expires_in_secs.unwrap_or(DEFAULT_HOLD_TTL_SECS);
+    if !(1..=MAX_HOLD_TTL_SECS).contains(&ttl) {
+        return Err(HoldError::Validation {
+            field: "expires_in_secs",
+            message: "expires_in_secs must be between 1 and 2592000 (30 days)",
+        });
+    }
+    if !is_allowed(allowed_account_ids, req.account_id) {
+        return Err(HoldError::AccountNotFound);
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| HoldError::Internal)?;
+
+    let account = lock_account_for_update(&mut tx, business_id, req.account_id)
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::AccountNotFound)?;
+    // A hold reserves money for a later debit, so it follows the debit rules.
+    account.ensure_can_debit()?;
+
+    if account.available < req.amount {
+        return Err(HoldError::InsufficientFunds);
+    }
+    // The other limits apply to the capture, which is when the money leaves the account.
+    if account.limits.min_balance.is_some_and(|min| account.net_balance - req.amount < min) {
+        return Err(HoldError::LimitExceeded(AccountLimit::MinBalance));
+    }
+
+    db::adjust_held_amount(&mut *tx, account.id, req.amount)
+        .await
+        .map_err(|_| HoldError::Internal)?;
+
+    let currency = &account.currency;
+    let hold = db::insert_hold(&mut *tx, business_id, account.id, req.amount, currency, ttl)
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::Internal)?;
+
+    tx.commit().await.map_err(|_| HoldError::Internal)?;
+
+    Ok(hold)
+}
+
+/// Captures `req.amount` (default: all) of an active hold as a debit of the held account, or
+/// as a transfer to `req.dest_account_id`, and releases the rest of the hold.
+pub async fn capture_hold(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    id: Uuid,
+    req: &CaptureHoldRequest,
+) -> Result<(Hold, Transaction), HoldError> {
+    let mut tx = pool.begin().await.map_err(|_| HoldError::Internal)?;
+
+    // The hold is locked before the accounts, like the original transaction in reversals.
+    let (hold, expired) = db::lock_hold_for_update(&mut *tx, business_id, id)
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::HoldNotFound)?;
+
+    if !is_allowed(allowed_account_ids, hold.account_id) {
+        return Err(HoldError::HoldNotFound);
+    }
+    ensure_active(&hold, expired)?;
+
+    let amount = req.amount.unwrap_or(hold.amount);
+    if amount <= 0 || amount > hold.amount {
+        return Err(HoldError::Validation {
+            field: "amount",
+            message: "amount must be > 0 and at most the held amount",
+        });
+    }
+    if req.dest_account_id == Some(hold.account_id) {
+        return Err(HoldError::Validation {
+            field: "dest_account_id",
+            message: "dest_account_id must differ from the held account",
+        });
+    }
+    if req.dest_account_id.is_some_and(|d| !is_allowed(allowed_account_ids, d)) {
+        return Err(HoldError::AccountNotFound);
+    }
+
+    let source_id = hold.account_id;
+    let (mut source, fx) = match req.dest_account_id {
+        Some(dest_id) => {
+            let (source, dest) =
+                lock_transfer_accounts(&mut tx, business_id, source_id, dest_id).await?;
+            let fx = if source.currency.trim() != dest.currency.trim() {
+                let conversion = convert_for_transfer(
+                    &mut tx,
+                    business_id,
+                    &source.currency,
+                    &dest.currency,
+                    None,
+                    amount,
+                )
+                .await?;
+                Some(conversion)
+            } else {
+                None
+            };
+            (source, fx)
+        }
+        None => {
+            let source = lock_account_for_update(&mut tx, business_id, source_id)
+                .await
+                .map_err(|_| HoldError::Internal)?
+                .ok_or(HoldError::AccountNotFound)?;
+            source.ensure_can_debit()?;
+            (source, None)
+        }
+    };
+
+    // The whole hold is released, so its amount is spendable by the capture itself.
+    source.available += hold.amount;
+    source.net_balance += hold.amount;
+    if source.available < amount {
+        return Err(HoldError::InsufficientFunds);
+    }
+    check_outflow_limits(&mut tx, &source, amount).await?;
+
+    db::adjust_held_amount(&mut *tx, source_id, -hold.amount)
+        .await
+        .map_err(|_| HoldError::Internal)?;
+
+    let tx_type = match req.dest_account_id {
+        Some(_) => TransactionType::Transfer,
+        None => TransactionType::Debit,
+    };
+    let transaction = record_transaction(
+        &mut tx,
+        business_id,
+        &NewTransaction {
+            tx_type,
+            source_account_id: Some(source_id),
+            dest_account_id: req.dest_account_id,
+            amount,
+            currency: &source.currency,
+            fx: fx.as_ref(),
+            reversal_of: None,
+            metadata: None,
+            external_reference: None,
+            description: None,
+            counterparty: None,
+        },
+    )
+    .await?;
+
+    let hold = db::release_hold(&mut *tx, id, HoldStatus::Captured, Some((amount, transaction.id)))
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::Internal)?;
+
+    enqueue_transaction_created_events(&mut tx, business_id, &transaction)
+        .await
+        .map_err(|_| HoldError::Internal)?;
+
+    tx.commit().await.map_err(|_| HoldError::Internal)?;
+
+    Ok((hold, transaction))
+}
+
+/// Releases an active hold without moving money.
+pub async fn void_hold(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    id: Uuid,
+) -> Result<Hold, HoldError> {
+    let mut tx = pool.begin().await.map_err(|_| HoldError::Internal)?;
+
+    let (hold, _) = db::lock_hold_for_update(&mut *tx, business_id, id)
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::HoldNotFound)?;
+
+    if !is_allowed(allowed_account_ids, hold.account_id) {
+        return Err(HoldError::HoldNotFound);
+    }
+    // Voiding a hold that is past expiry but not yet released by the worker is allowed.
+    ensure_active(&hold, false)?;
+
+    let hold = release(&mut tx, &hold, HoldStatus::Voided).await?;
+
+    tx.commit().await.map_err(|_| HoldError::Internal)?;
+
+    Ok(hold)
+}
+
+/// Releases the hold if it is still active and past its expiry; returns whether it did.
+/// Used by the hold worker.
+pub async fn expire_hold(pool: &PgPool, business_id: Uuid, id: Uuid) -> Result<bool, HoldError> {
+    let mut tx = pool.begin().await.map_err(|_| HoldError::Internal)?;
+
+    let Some((hold, expired)) = db::lock_hold_for_update(&mut *tx, business_id, id)
+        .await
+        .map_err(|_| HoldError::Internal)?
+    else {
+        return Ok(false);
+    };
+
+    // Captured or voided since it was listed.
+    if hold.status != HoldStatus::Active || !expired {
+        return Ok(false);
+    }
+
+    release(&mut tx, &hold, HoldStatus::Expired).await?;
+
+    tx.commit().await.map_err(|_| HoldError::Internal)?;
+
+    Ok(true)
+}
+
+async fn release(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    hold: &Hold,
+    status: HoldStatus,
+) -> Result<Hold, HoldError> {
+    db::adjust_held_amount(&mut **tx, hold.account_id, -hold.amount)
+        .await
+        .map_err(|_| HoldError::Internal)?;
+
+    db::release_hold(&mut **tx, hold.id, status, None)
+        .await
+        .map_err(|_| HoldError::Internal)?
+        .ok_or(HoldError::Internal)
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use sqlx::{Postgres, Transaction as SqlxTransaction};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum LedgerError {
+    Unbalanced,
+    Internal,
+}
+
+// One leg of a journal entry. Positive amounts increase the account balance.
+#[derive(Debug, Clone)]
+pub struct Posting {
+    pub account_id: Uuid,
+    pub currency: String,
+    pub amount: i64,
+}
+
+impl Posting {
+    pub fn new(account_id: Uuid, currency: &str, amount: i64) -> Self {
+        Self {
+            account_id,
+            currency: currency.trim().to_string(),
+            amount,
+        }
+    }
+}
+
+fn ensure_balanced(postings: &[Posting]) -> Result<(), LedgerError> {
+    let mut totals: HashMap<&str, i64> = HashMap::new();
+
+    for p in postings {
+        if p.amount == 0 {
+            return Err(LedgerError::Unbalanced);
+        }
+        let total = totals.entry(p.currency.as_str()).or_insert(0);
+        *total = total.checked_add(p.amount).ok_or(LedgerError::Unbalanced)?;
+    }
+
+    if postings.len() < 2 || totals.values().any(|t| *t != 0) {
+        return Err(LedgerError::Unbalanced);
+    }
+
+    Ok(())
+}
+
+// Get-or-create of the system account named `system:<kind>:<CCY>`.
+async fn system_account_id(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    kind: &str,
+    currency: &str,
+) -> Result<Uuid, sqlx::Error> {
+    let currency = currency.trim();
+    let name = format!("system:{kind}:{currency}");
+
+    let insert = r#"
+        INSERT INTO accounts (business_id, name, currency, balance, is_system)
+        VALUES ($1, $2, $3, 0, true)
+        ON CONFLICT (business_id, name) WHERE is_system DO NOTHING
+    "#;
+
+    sqlx::query(insert)
+        .bind(business_id)
+        .bind(&name)
+        .bind(currency)
+        .execute(&mut **tx)
+        .await?;
+
+    let select = r#"
+        SELECT id
+        FROM accounts
+        WHERE business_id = $1 AND name = $2 AND is_system
+    "#;
+
+    let (id,): (Uuid,) = sqlx::query_as(select)
+        .bind(business_id)
+        .bind(&name)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    Ok(id)
+}
+
+/// Returns the system ("world") account for a business + currency, creating it on first use.
+///
+/// Credits and debits post their other leg here.
+pub async fn external_account_id(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    currency: &str,
+) -> Result<Uuid, sqlx::Error> {
+    system_account_id(tx, business_id, "external", currency).await
+}
+
+/// Returns the FX position account for a business + currency, creating it on first use.
+///
+/// A cross-currency transfer posts `+amount` to the source currency's FX account and
+/// `-dest_amount` to the destination currency's, so each currency still balances.
+pub async fn fx_account_id(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    currency: &str,
+) -> Result<Uuid, sqlx::Error> {
+    system_account_id(tx, business_id, "fx", currency).await
+}
+
+// Returns the new cached balance, or None for system accounts.
+async fn apply_posting_to_balance(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    posting: &Posting,
+) -> Result<Option<i64>, sqlx::Error> {
+    // System account balances are derived from postings on read, not cached.
+    let q = r#"
+        UPDATE accounts
+        SET balance = balance + $1
+        WHERE id = $2 AND NOT is_system
+        RETURNING balance
+    "#;
+
+    let row: Option<(i64,)> = sqlx::query_as(q)
+        .bind(posting.amount)
+        .bind(posting.account_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    Ok(row.map(|(balance,)| balance))
+}
+
+/// Writes a balanced journal entry for `transaction_id` and updates the cached balances.
+///
+/// Callers must already hold row locks on the customer accounts being posted to.
+pub async fn post_journal_entry(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    transaction_id: Uuid,
+    postings: &[Posting],
+) -> Result<Uuid, LedgerError> {
+    ensure_balanced(postings)?;
+
+    let q = r#"
+        INSERT INTO journal_entries (business_id, transaction_id)
+        VALUES ($1, $2)
+        RETURNING id
+    "#;
+
+    let (entry_id,): (Uuid,) = sqlx::query_as(q)
+        .bind(business_id)
+        .bind(transaction_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|_| LedgerError::Internal)?;
+
+    let q = r#"
+        INSERT INTO postings (journal_entry_id, account_id, currency, amount, balance_after)
+        VALUES ($1, $2, $3, $4, $5)
+    "#;
+
+    for p in postings {
+        let balance_after = apply_posting_to_balance(tx, p)
+            .await
+            .map_err(|_| LedgerError::Internal)?;
+
+        sqlx::query(q)
+            .bind(entry_id)
+            .bind(p.account_id)
+            .bind(&p.currency)
+            .bind(p.amount)
+            .bind(balance_after)
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| LedgerError::Internal)?;
+    }
+
+    Ok(entry_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn posting(currency: &str, amount: i64) -> Posting {
+        Posting::new(Uuid::nil(), currency, amount)
+    }
+
+    fn is_balanced(postings: &[Posting]) -> bool {
+        ensure_balanced(postings).is_ok()
+    }
+
+    #[test]
+    fn accepts_balanced_entries() {
+        assert!(is_balanced(&[posting("USD", -500), posting("USD", 500)]));
+        assert!(is_balanced(&[
+            posting("USD", -500),
+            posting("USD", 300),
+            posting("USD", 200),
+        ]));
+    }
+
+    #[test]
+    fn accepts_multi_currency_entry_balanced_per_currency() {
+        // An FX transfer: USD leaves the source into the USD FX account, EUR comes out of the
+        // EUR FX account into the destination.
+        assert!(is_balanced(&[
+            posting("USD", -1000),
+            posting("USD", 1000),
+            posting("EUR", -920),
+            posting("EUR", 920),
+        ]));
+        // CHAR(3) padding does not split a currency.
+        assert!(is_balanced(&[posting("USD ", -1), posting("USD", 1)]));
+    }
+
+    #[test]
+    fn rejects_unbalanced_entries() {
+        assert!(!is_balanced(&[posting("USD", -500), posting("USD", 499)]));
+        assert!(!is_balanced(&[posting("USD", 500), posting("USD", 500)]));
+    }
+
+    #[test]
+    fn rejects_per_currency_imbalance_that_nets_to_zero() {
+        assert!(!is_balanced(&[posting("USD", -1000), posting("EUR", 1000)]));
+        assert!(!is_balanced(&[
+            posting("USD", -1000),
+            posting("USD", 920),
+            posting("EUR", -920),
+            posting("EUR", 1000),
+        ]));
+    }
+
+    #[test]
+    fn rejects_empty_single_and_zero_postings() {
+        assert!(!is_balanced(&[]));
+        assert!(!is_balanced(&[posting("USD", 0)]));
+        assert!(!is_balanced(&[posting("USD", 0), posting("USD", 0)]));
+        assert!(!is_balanced(&[posting("USD", -5), posting("USD", 5), posting("USD", 0)]));
+    }
+
+    #[test]
+    fn rejects_overflowing_totals() {
+        assert!(!is_balanced(&[
+            posting("USD", i64::MAX),
+            posting("USD", 1),
+            posting("USD", i64::MIN),
+        ]));
+    }
+}
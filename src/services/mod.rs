@@ -1,3 +1,9 @@
+pub mod account_service;
+pub mod api_key_service;
+pub mod fx_service;
+pub mod hold_service;
+pub mod ledger_service;
+pub mod scheduled_transaction_service;
 pub mod transaction_service;
 pub mod webhook_service;
 
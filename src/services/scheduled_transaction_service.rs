@@ -0,0 +1,321 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::{PgPool, Postgres, Transaction as SqlxTransaction};
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::ApiError;
+use crate::models::scheduled_transaction::{
+    CreateScheduledTransactionRequest, ScheduleStatus, ScheduledRunStatus, ScheduledTransaction,
+};
+use crate::models::transaction::CreateTransactionRequest;
+use crate::services::transaction_service::{
+    TransactionError, create_scheduled_transaction, validate_request,
+};
+use crate::services::webhook_service::enqueue_scheduled_transaction_failed_events;
+
+#[derive(Debug)]
+pub enum ScheduleError {
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
+    AccountNotFound,
+    ScheduleNotFound,
+    // Already completed or cancelled.
+    NotActive(&'static str),
+    Internal,
+}
+
+impl From<TransactionError> for ScheduleError {
+    fn from(err: TransactionError) -> Self {
+        match err {
+            TransactionError::Validation { field, message } => {
+                ScheduleError::Validation { field, message }
+            }
+            TransactionError::AccountNotFound => ScheduleError::AccountNotFound,
+            _ => ScheduleError::Internal,
+        }
+    }
+}
+
+// Tolerated clock skew between the client and the server for `start_at`.
+const START_AT_GRACE_SECS: i64 = 60;
+
+// Next occurrence after the one being run, or None if the schedule is done.
+fn next_run_at(schedule: &ScheduledTransaction) -> Option<DateTime<Utc>> {
+    let frequency = schedule.frequency?;
+    let n = u32::try_from(schedule.run_count + 1).ok()?;
+    let next = frequency.occurrence(schedule.start_at, n)?;
+    Some(next).filter(|next| schedule.end_at.is_none_or(|end| *next <= end))
+}
+
+/// Creates a one-off or recurring schedule for a transaction template. The request is
+/// validated like `POST /api/transactions`; funds and currencies are only checked when it runs.
+pub async fn create_schedule(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    req: &CreateScheduledTransactionRequest,
+) -> Result<ScheduledTransaction, ScheduleError> {
+    let template = CreateTransactionRequest {
+        tx_type: req.tx_type,
+        amount: req.amount,
+        source_account_id: req.source_account_id,
+        dest_account_id: req.dest_account_id,
+        fx_rate: None,
+        quote_id: None,
+        metadata: None,
+        external_reference: None,
+        description: None,
+        counterparty: None,
+    };
+    validate_request(&template, allowed_account_ids)?;
+
+    if req.start_at < Utc::now() - Duration::seconds(START_AT_GRACE_SECS) {
+        return Err(ScheduleError::Validation {
+            field: "start_at",
+            message: "start_at must not be in the past",
+        });
+    }
+    if let Some(end_at) = req.end_at {
+        if req.frequency.is_none() {
+            return Err(ScheduleError::Validation {
+                field: "end_at",
+                message: "end_at requires a frequency",
+            });
+        }
+        if end_at < req.start_at {
+            return Err(ScheduleError::Validation {
+                field: "end_at",
+                message: "end_at must not be before start_at",
+            });
+        }
+    }
+
+    for account_id in req.source_account_id.into_iter().chain(req.dest_account_id) {
+        db::get_account(pool, business_id, account_id)
+            .await
+            .map_err(|_| ScheduleError::Internal)?
+            .ok_or(ScheduleError::AccountNotFound)?;
+    }
+
+    db::insert_scheduled_transaction(pool, business_id, req)
+        .await
+        .map_err(|_| ScheduleError::Internal)?
+        .ok_or(ScheduleError::Internal)
+}
+
+/// Stops an active schedule; runs already executed are kept.
+pub async fn cancel_schedule(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    id: Uuid,
+) -> Result<ScheduledTransaction, ScheduleError> {
+    let mut tx = pool.begin().await.map_err(|_| ScheduleError::Internal)?;
+
+    let (schedule, _) = db::lock_scheduled_transaction_for_update(&mut *tx, business_id, id)
+        .await
+        .map_err(|_| ScheduleError::Internal)?
+        .ok_or(ScheduleError::ScheduleNotFound)?;
+
+    let allowed = |account_id| allowed_account_ids.is_none_or(|ids| ids.contains(&account_id));
+    if !schedule.account_ids().all(allowed) {
+        return Err(ScheduleError::ScheduleNotFound);
+    }
+    match schedule.status {
+        ScheduleStatus::Active => {}
+        ScheduleStatus::Completed => {
+            return Err(ScheduleError::NotActive("scheduled transaction has completed"));
+        }
+        ScheduleStatus::Cancelled => {
+            return Err(ScheduleError::NotActive("scheduled transaction has been cancelled"));
+        }
+    }
+
+    let schedule = db::cancel_scheduled_transaction(&mut *tx, id)
+        .await
+        .map_err(|_| ScheduleError::Internal)?
+        .ok_or(ScheduleError::Internal)?;
+
+    tx.commit().await.map_err(|_| ScheduleError::Internal)?;
+
+    Ok(schedule)
+}
+
+/// Executes the schedule's next occurrence if it is still active and due; returns whether it
+/// ran. Used by the scheduler.
+///
+/// The transaction is created in the same DB transaction that holds the schedule row lock and
+/// records the run, so an occurrence moves money exactly once: a crash before the commit
+/// leaves neither the transaction nor the run behind, and the next poll retries it.
+/// A rejected request (e.g. insufficient funds) is recorded as a failed run, a
+/// `scheduled_transaction.failed` webhook is enqueued and the schedule moves on; internal
+/// errors leave the occurrence due so the next poll retries it.
+///
+/// Occurrences missed while the scheduler was not running are not paid out back-to-back: every
+/// due occurrence but the latest is recorded as a failed `occurrence_missed` run.
+pub async fn run_due_schedule(
+    pool: &PgPool,
+    business_id: Uuid,
+    id: Uuid,
+) -> Result<bool, ScheduleError> {
+    let mut tx = pool.begin().await.map_err(|_| ScheduleError::Internal)?;
+
+    // Held until the run is recorded, so a concurrent cancel waits for it.
+    let Some((mut schedule, due)) =
+        db::lock_scheduled_transaction_for_update(&mut *tx, business_id, id)
+            .await
+            .map_err(|_| ScheduleError::Internal)?
+    else {
+        return Ok(false);
+    };
+
+    let mut scheduled_for = match schedule.next_run_at {
+        Some(at) if schedule.status == ScheduleStatus::Active && due => at,
+        // Ran, completed or cancelled since it was listed.
+        _ => return Ok(false),
+    };
+
+    let now = Utc::now();
+    while let Some(next) = next_run_at(&schedule).filter(|next| *next <= now) {
+        let missed = json!({
+            "code": "occurrence_missed",
+            "error": "the occurrence was not run on time and a later one is already due",
+        });
+        record_run(&mut tx, &schedule, scheduled_for, None, Some(missed), Some(next)).await?;
+        schedule.run_count += 1;
+        scheduled_for = next;
+    }
+
+    let request = schedule.transaction_request();
+    let outcome = create_scheduled_transaction(&mut tx, business_id, &request)
+        .await
+        .map_err(|_| ScheduleError::Internal)?;
+
+    let (transaction_id, error) = match outcome {
+        Ok(transaction) => (Some(transaction.id), None),
+        Err(TransactionError::Internal) => return Err(ScheduleError::Internal),
+        Err(err) => (None, Some(ApiError::from(err).body())),
+    };
+
+    let next = next_run_at(&schedule);
+    record_run(&mut tx, &schedule, scheduled_for, transaction_id, error, next).await?;
+
+    tx.commit().await.map_err(|_| ScheduleError::Internal)?;
+
+    Ok(true)
+}
+
+// Records occurrence `schedule.run_count` as succeeded (with `transaction_id`) or failed (with
+// `error`), moves the schedule on to `next_run_at` and enqueues the failure webhook.
+async fn record_run(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    schedule: &ScheduledTransaction,
+    scheduled_for: DateTime<Utc>,
+    transaction_id: Option<Uuid>,
+    error: Option<serde_json::Value>,
+    next_run_at: Option<DateTime<Utc>>,
+) -> Result<(), ScheduleError> {
+    let status = match error {
+        None => ScheduledRunStatus::Succeeded,
+        Some(_) => ScheduledRunStatus::Failed,
+    };
+
+    let error_json = error.map(|e| e.to_string());
+    let run = db::insert_scheduled_transaction_run(
+        &mut **tx,
+        schedule,
+        scheduled_for,
+        status,
+        transaction_id,
+        error_json.as_deref(),
+    )
+    .await
+    .map_err(|_| ScheduleError::Internal)?
+    .ok_or(ScheduleError::Internal)?;
+
+    db::advance_scheduled_transaction(&mut **tx, schedule.id, next_run_at)
+        .await
+        .map_err(|_| ScheduleError::Internal)?;
+
+    if status == ScheduledRunStatus::Failed {
+        enqueue_scheduled_transaction_failed_events(tx, schedule.business_id, schedule, &run)
+            .await
+            .map_err(|_| ScheduleError::Internal)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::scheduled_transaction::ScheduleFrequency;
+    use crate::models::transaction::TransactionType;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn schedule(
+        frequency: Option<ScheduleFrequency>,
+        start_at: &str,
+        end_at: Option<&str>,
+        run_count: i32,
+    ) -> ScheduledTransaction {
+        let start_at = at(start_at);
+        ScheduledTransaction {
+            id: Uuid::nil(),
+            business_id: Uuid::nil(),
+            tx_type: TransactionType::Credit,
+            source_account_id: None,
+            dest_account_id: Some(Uuid::nil()),
+            amount: 100,
+            frequency,
+            start_at,
+            end_at: end_at.map(at),
+            status: ScheduleStatus::Active,
+            run_count,
+            next_run_at: None,
+            last_run_at: None,
+            created_at: start_at,
+            updated_at: start_at,
+        }
+    }
+
+    #[test]
+    fn one_off_schedule_has_no_next_run() {
+        let s = schedule(None, "2026-01-31T09:00:00Z", None, 0);
+        assert_eq!(next_run_at(&s), None);
+    }
+
+    #[test]
+    fn next_run_follows_the_run_count() {
+        let monthly = Some(ScheduleFrequency::Monthly);
+        let s = schedule(monthly, "2026-01-31T09:00:00Z", None, 0);
+        assert_eq!(next_run_at(&s), Some(at("2026-02-28T09:00:00Z")));
+        let s = schedule(monthly, "2026-01-31T09:00:00Z", None, 1);
+        assert_eq!(next_run_at(&s), Some(at("2026-03-31T09:00:00Z")));
+    }
+
+    #[test]
+    fn next_run_stops_at_end_at() {
+        let daily = Some(ScheduleFrequency::Daily);
+        let end = Some("2026-01-03T09:00:00Z");
+
+        // An occurrence exactly at end_at still runs.
+        let s = schedule(daily, "2026-01-01T09:00:00Z", end, 1);
+        assert_eq!(next_run_at(&s), Some(at("2026-01-03T09:00:00Z")));
+        let s = schedule(daily, "2026-01-01T09:00:00Z", end, 2);
+        assert_eq!(next_run_at(&s), None);
+
+        // Clamped month-end occurrences are compared after clamping.
+        let monthly = Some(ScheduleFrequency::Monthly);
+        let s = schedule(monthly, "2026-01-31T09:00:00Z", Some("2026-02-28T09:00:00Z"), 0);
+        assert_eq!(next_run_at(&s), Some(at("2026-02-28T09:00:00Z")));
+        let s = schedule(monthly, "2026-01-31T09:00:00Z", Some("2026-02-28T08:59:59Z"), 0);
+        assert_eq!(next_run_at(&s), None);
+    }
+}
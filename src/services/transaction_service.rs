@@ -1,19 +1,91 @@
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
 
-use sqlx::{PgPool, Postgres, Transaction as SqlxTransaction};
+use sqlx::{Connection, PgPool, Postgres, Transaction as SqlxTransaction};
 
-use crate::models::transaction::{CreateTransactionRequest, Transaction, TransactionType};
-use crate::services::webhook_service::enqueue_transaction_created_events_best_effort;
+use crate::db;
+use crate::metadata::{self, Metadata};
+use crate::models::account::{AccountLimit, AccountLimits, AccountStatus};
+use crate::models::fx::FxConversion;
+use crate::models::transaction::{
+    BatchMode, Counterparty, CreateTransactionRequest, ReverseTransactionRequest, Transaction,
+    TransactionResponse, TransactionType,
+};
+use crate::services::fx_service::{FxError, FxRate, conversion_for};
+use crate::services::ledger_service::{
+    Posting, external_account_id, fx_account_id, post_journal_entry,
+};
+use crate::services::webhook_service::{
+    enqueue_transaction_created_events, enqueue_transaction_reversed_events,
+};
 
 #[derive(Debug)]
 pub enum TransactionError {
-    BadRequest(&'static str),
-    NotFound,
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
+    AccountNotFound,
+    // The account to debit is frozen.
+    AccountFrozen,
+    AccountClosed,
     InsufficientFunds,
+    CurrencyMismatch,
+    IdempotencyKeyReused,
+    QuoteNotFound,
+    QuoteAlreadyUsed,
+    QuoteExpired,
+    TransactionNotFound,
+    AlreadyReversed,
+    // One of the source account's risk limits would be broken.
+    LimitExceeded(AccountLimit),
     Internal,
 }
 
+impl From<FxError> for TransactionError {
+    fn from(err: FxError) -> Self {
+        match err {
+            FxError::Validation { field, message } => {
+                TransactionError::Validation { field, message }
+            }
+            FxError::AccountNotFound => TransactionError::AccountNotFound,
+            FxError::CurrencyMismatch => TransactionError::CurrencyMismatch,
+            FxError::Internal => TransactionError::Internal,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CreateTransactionOutcome {
+    Created(Box<Transaction>),
+    // Stored response body of an earlier request with the same Idempotency-Key.
+    Replayed(serde_json::Value),
+}
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+fn validate_idempotency_key(key: &str) -> Result<(), TransactionError> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(TransactionError::Validation {
+            field: "Idempotency-Key",
+            message: "Idempotency-Key must be 1-255 characters",
+        });
+    }
+    if !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(TransactionError::Validation {
+            field: "Idempotency-Key",
+            message: "Idempotency-Key must be printable ASCII without spaces",
+        });
+    }
+    Ok(())
+}
+
+fn request_fingerprint(req: &CreateTransactionRequest) -> Result<String, TransactionError> {
+    let bytes = serde_json::to_vec(req).map_err(|_| TransactionError::Internal)?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
 fn order_uuids(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
     if a.as_bytes() <= b.as_bytes() {
         (a, b)
@@ -22,300 +94,1249 @@ fn order_uuids(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
     }
 }
 
-async fn lock_account_for_update(
+// N-account version of `order_uuids`: the distinct ids in the same global lock order.
+fn lock_order(ids: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = ids.into_iter().collect();
+    ids.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+    ids.dedup();
+    ids
+}
+
+// An account row locked by `lock_account_for_update`.
+pub(crate) struct LockedAccount {
+    pub id: Uuid,
+    pub currency: String,
+    // The balance minus active holds plus the overdraft limit, which is what balance checks
+    // compare against.
+    pub available: i64,
+    // The balance minus active holds, which `min_balance` applies to.
+    pub net_balance: i64,
+    pub status: AccountStatus,
+    pub limits: AccountLimits,
+}
+
+impl LockedAccount {
+    // Frozen accounts cannot send money; closed accounts take part in nothing.
+    pub(crate) fn ensure_can_debit(&self) -> Result<(), TransactionError> {
+        match self.status {
+            AccountStatus::Active => Ok(()),
+            AccountStatus::Frozen => Err(TransactionError::AccountFrozen),
+            AccountStatus::Closed => Err(TransactionError::AccountClosed),
+        }
+    }
+
+    pub(crate) fn ensure_can_credit(&self) -> Result<(), TransactionError> {
+        match self.status {
+            AccountStatus::Active | AccountStatus::Frozen => Ok(()),
+            AccountStatus::Closed => Err(TransactionError::AccountClosed),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LockedAccountRow {
+    id: Uuid,
+    currency: String,
+    net_balance: i64,
+    overdraft_limit: i64,
+    status: String,
+    max_transaction_amount: Option<i64>,
+    max_daily_outflow_count: Option<i64>,
+    max_daily_outflow_amount: Option<i64>,
+    min_balance: Option<i64>,
+}
+
+pub(crate) async fn lock_account_for_update(
     tx: &mut SqlxTransaction<'_, Postgres>,
     business_id: Uuid,
     account_id: Uuid,
-) -> Result<Option<(Uuid, String, i64)>, sqlx::Error> {
-    // Returns: (id, currency, balance)
+) -> Result<Option<LockedAccount>, sqlx::Error> {
     let q = r#"
-        SELECT id, currency, balance
+        SELECT
+            id, currency, balance - held_amount AS net_balance, overdraft_limit,
+            status::text AS status, max_transaction_amount, max_daily_outflow_count,
+            max_daily_outflow_amount, min_balance
         FROM accounts
-        WHERE id = $1 AND business_id = $2
+        WHERE id = $1 AND business_id = $2 AND NOT is_system
         FOR UPDATE
     "#;
 
-    let row: Option<(Uuid, String, i64)> = sqlx::query_as(q)
+    let row: Option<LockedAccountRow> = sqlx::query_as(q)
         .bind(account_id)
         .bind(business_id)
         .fetch_optional(&mut **tx)
         .await?;
 
-    Ok(row)
+    Ok(row.and_then(|row| {
+        Some(LockedAccount {
+            id: row.id,
+            currency: row.currency,
+            available: row.net_balance + row.overdraft_limit,
+            net_balance: row.net_balance,
+            status: AccountStatus::from_db_str(&row.status)?,
+            limits: AccountLimits {
+                max_transaction_amount: row.max_transaction_amount,
+                max_daily_outflow_count: row.max_daily_outflow_count,
+                max_daily_outflow_amount: row.max_daily_outflow_amount,
+                min_balance: row.min_balance,
+            },
+        })
+    }))
 }
 
-async fn update_balance(
+// Checks the source account's risk limits for sending `amount`. Runs under the account row
+// lock, so concurrent debits cannot both pass the daily limits; earlier items of the same
+// batch are already in the daily totals.
+pub(crate) async fn check_outflow_limits(
     tx: &mut SqlxTransaction<'_, Postgres>,
-    account_id: Uuid,
-    new_balance: i64,
-) -> Result<(), sqlx::Error> {
-    let q = r#"
-        UPDATE accounts
-        SET balance = $1
-        WHERE id = $2
-    "#;
+    source: &LockedAccount,
+    amount: i64,
+) -> Result<(), TransactionError> {
+    let limits = &source.limits;
 
-    sqlx::query(q)
-        .bind(new_balance)
-        .bind(account_id)
-        .execute(&mut **tx)
-        .await?;
+    if limits.max_transaction_amount.is_some_and(|max| amount > max) {
+        return Err(TransactionError::LimitExceeded(AccountLimit::MaxTransactionAmount));
+    }
+    if limits.min_balance.is_some_and(|min| source.net_balance - amount < min) {
+        return Err(TransactionError::LimitExceeded(AccountLimit::MinBalance));
+    }
+
+    if limits.max_daily_outflow_count.is_none() && limits.max_daily_outflow_amount.is_none() {
+        return Ok(());
+    }
+    let (count, total) = db::daily_outflow(&mut **tx, source.id)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+    if limits.max_daily_outflow_count.is_some_and(|max| count + 1 > max) {
+        return Err(TransactionError::LimitExceeded(AccountLimit::MaxDailyOutflowCount));
+    }
+    if limits.max_daily_outflow_amount.is_some_and(|max| total + amount > max) {
+        return Err(TransactionError::LimitExceeded(AccountLimit::MaxDailyOutflowAmount));
+    }
 
     Ok(())
 }
 
+// Locks both accounts of a transfer in a deterministic order (by UUID bytes) to avoid
+// deadlocks, and checks their status allows the transfer. Returns the source and the
+// destination.
+pub(crate) async fn lock_transfer_accounts(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    source_id: Uuid,
+    dest_id: Uuid,
+) -> Result<(LockedAccount, LockedAccount), TransactionError> {
+    let (first, second) = order_uuids(source_id, dest_id);
+
+    let first_row = lock_account_for_update(tx, business_id, first)
+        .await
+        .map_err(|_| TransactionError::Internal)?
+        .ok_or(TransactionError::AccountNotFound)?;
+    let second_row = lock_account_for_update(tx, business_id, second)
+        .await
+        .map_err(|_| TransactionError::Internal)?
+        .ok_or(TransactionError::AccountNotFound)?;
+
+    let (source, dest) = if first_row.id == source_id {
+        (first_row, second_row)
+    } else {
+        (second_row, first_row)
+    };
+
+    source.ensure_can_debit()?;
+    dest.ensure_can_credit()?;
+
+    Ok((source, dest))
+}
+
+// Columns of a new `transactions` row; id and created_at are assigned by the DB.
+pub(crate) struct NewTransaction<'a> {
+    pub tx_type: TransactionType,
+    pub source_account_id: Option<Uuid>,
+    pub dest_account_id: Option<Uuid>,
+    pub amount: i64,
+    pub currency: &'a str,
+    pub fx: Option<&'a FxConversion>,
+    pub reversal_of: Option<Uuid>,
+    pub metadata: Option<&'a Metadata>,
+    pub external_reference: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub counterparty: Option<&'a Counterparty>,
+}
+
 async fn insert_transaction_row(
     tx: &mut SqlxTransaction<'_, Postgres>,
     business_id: Uuid,
-    tx_type: TransactionType,
-    source_account_id: Option<Uuid>,
-    dest_account_id: Option<Uuid>,
-    amount: i64,
+    new: &NewTransaction<'_>,
 ) -> Result<(Uuid, DateTime<Utc>), sqlx::Error> {
     let q = r#"
-        INSERT INTO transactions (business_id, type, source_account_id, dest_account_id, amount)
-        VALUES ($1, $2::transaction_type, $3, $4, $5)
+        INSERT INTO transactions (
+            business_id, type, source_account_id, dest_account_id, amount, currency,
+            dest_amount, dest_currency, fx_rate, reversal_of, metadata, external_reference,
+            description, counterparty_name, counterparty_account_identifier,
+            counterparty_reference
+        )
+        VALUES (
+            $1, $2::transaction_type, $3, $4, $5, $6, $7, $8, $9::numeric, $10,
+            COALESCE($11::jsonb, '{}'), $12, $13, $14, $15, $16
+        )
         RETURNING id, created_at
     "#;
 
+    let metadata_json = match new.metadata {
+        Some(m) => Some(serde_json::to_string(m).map_err(|e| sqlx::Error::Encode(e.into()))?),
+        None => None,
+    };
+
     let (id, created_at): (Uuid, DateTime<Utc>) = sqlx::query_as(q)
         .bind(business_id)
-        .bind(tx_type.as_db_str())
-        .bind(source_account_id)
-        .bind(dest_account_id)
-        .bind(amount)
+        .bind(new.tx_type.as_db_str())
+        .bind(new.source_account_id)
+        .bind(new.dest_account_id)
+        .bind(new.amount)
+        .bind(new.currency.trim())
+        .bind(new.fx.map(|f| f.dest_amount))
+        .bind(new.fx.map(|f| f.dest_currency.as_str()))
+        .bind(new.fx.map(|f| f.rate.as_str()))
+        .bind(new.reversal_of)
+        .bind(metadata_json)
+        .bind(new.external_reference)
+        .bind(new.description)
+        .bind(new.counterparty.map(|c| c.name.as_str()))
+        .bind(new.counterparty.and_then(|c| c.account_identifier.as_deref()))
+        .bind(new.counterparty.and_then(|c| c.reference.as_deref()))
         .fetch_one(&mut **tx)
         .await?;
 
     Ok((id, created_at))
 }
 
-pub async fn create_transaction(
-    pool: &PgPool,
+// Inserts the transaction row and its journal entry; the postings update the cached balances
+// of the (already locked) accounts. Credits and debits balance against the currency's external
+// account, FX transfers through the per-currency FX position accounts.
+pub(crate) async fn record_transaction(
+    tx: &mut SqlxTransaction<'_, Postgres>,
     business_id: Uuid,
-    req: CreateTransactionRequest,
+    new: &NewTransaction<'_>,
 ) -> Result<Transaction, TransactionError> {
+    let (id, created_at) = insert_transaction_row(tx, business_id, new)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+
+    let (amount, currency) = (new.amount, new.currency);
+    let postings = match (new.source_account_id, new.dest_account_id, new.fx) {
+        (None, Some(dest_id), _) => {
+            let external_id = external_account_id(tx, business_id, currency)
+                .await
+                .map_err(|_| TransactionError::Internal)?;
+            vec![
+                Posting::new(dest_id, currency, amount),
+                Posting::new(external_id, currency, -amount),
+            ]
+        }
+        (Some(source_id), None, _) => {
+            let external_id = external_account_id(tx, business_id, currency)
+                .await
+                .map_err(|_| TransactionError::Internal)?;
+            vec![
+                Posting::new(source_id, currency, -amount),
+                Posting::new(external_id, currency, amount),
+            ]
+        }
+        (Some(source_id), Some(dest_id), None) => vec![
+            Posting::new(source_id, currency, -amount),
+            Posting::new(dest_id, currency, amount),
+        ],
+        (Some(source_id), Some(dest_id), Some(fx)) => {
+            let fx_source = fx_account_id(tx, business_id, currency)
+                .await
+                .map_err(|_| TransactionError::Internal)?;
+            let fx_dest = fx_account_id(tx, business_id, &fx.dest_currency)
+                .await
+                .map_err(|_| TransactionError::Internal)?;
+            vec![
+                Posting::new(source_id, currency, -amount),
+                Posting::new(fx_source, currency, amount),
+                Posting::new(fx_dest, &fx.dest_currency, -fx.dest_amount),
+                Posting::new(dest_id, &fx.dest_currency, fx.dest_amount),
+            ]
+        }
+        (None, None, _) => return Err(TransactionError::Internal),
+    };
+
+    post_journal_entry(tx, business_id, id, &postings)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+
+    Ok(Transaction {
+        id,
+        business_id,
+        tx_type: new.tx_type,
+        source_account_id: new.source_account_id,
+        dest_account_id: new.dest_account_id,
+        amount,
+        currency: currency.trim().to_string(),
+        fx: new.fx.cloned(),
+        reversal_of: new.reversal_of,
+        reversed_amount: 0,
+        metadata: new.metadata.cloned().unwrap_or_default(),
+        external_reference: new.external_reference.map(str::to_string),
+        description: new.description.map(str::to_string),
+        counterparty: new.counterparty.cloned(),
+        created_at,
+    })
+}
+
+// Rate and destination amount of a cross-currency transfer. Without an explicit rate the
+// business's rate table is used; no rate at all is a currency mismatch, as before FX existed.
+pub(crate) async fn convert_for_transfer(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    source_currency: &str,
+    dest_currency: &str,
+    explicit_rate: Option<FxRate>,
+    amount: i64,
+) -> Result<FxConversion, TransactionError> {
+    let rate = match explicit_rate {
+        Some(rate) => rate,
+        None => {
+            let (base, quote) = (source_currency.trim(), dest_currency.trim());
+            let stored = db::find_fx_rate(&mut **tx, business_id, base, quote)
+                .await
+                .map_err(|_| TransactionError::Internal)?;
+            stored
+                .as_deref()
+                .and_then(FxRate::parse)
+                .ok_or(TransactionError::CurrencyMismatch)?
+        }
+    };
+
+    Ok(conversion_for(amount, source_currency, dest_currency, rate)?)
+}
+
+// Consumes a quote for a transfer: the quote must be unused, unexpired and match the request
+// exactly, so the transfer executes at the locked rate or not at all.
+async fn consume_quote(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    quote_id: Uuid,
+    source_id: Uuid,
+    dest_id: Uuid,
+    amount: i64,
+) -> Result<FxConversion, TransactionError> {
+    let (quote, expired) = db::lock_fx_quote_for_update(&mut **tx, business_id, quote_id)
+        .await
+        .map_err(|_| TransactionError::Internal)?
+        .ok_or(TransactionError::QuoteNotFound)?;
+
+    if quote.used_at.is_some() {
+        return Err(TransactionError::QuoteAlreadyUsed);
+    }
+    if expired {
+        return Err(TransactionError::QuoteExpired);
+    }
+    if quote.source_account_id != source_id || quote.dest_account_id != dest_id {
+        return Err(TransactionError::Validation {
+            field: "quote_id",
+            message: "quote was issued for different accounts",
+        });
+    }
+    if quote.amount != amount {
+        return Err(TransactionError::Validation {
+            field: "amount",
+            message: "amount must match the quoted amount",
+        });
+    }
+
+    Ok(quote.conversion)
+}
+
+async fn claim_idempotency_key(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    key: &str,
+    request_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let q = r#"
+        INSERT INTO idempotency_keys (business_id, key, request_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (business_id, key) DO NOTHING
+    "#;
+
+    let result = sqlx::query(q)
+        .bind(business_id)
+        .bind(key)
+        .bind(request_hash)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+async fn load_idempotency_key(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    key: &str,
+) -> Result<(String, Option<serde_json::Value>), sqlx::Error> {
+    // Returns: (request_hash, response_body)
+    let q = r#"
+        SELECT request_hash, response_body
+        FROM idempotency_keys
+        WHERE business_id = $1 AND key = $2
+    "#;
+
+    sqlx::query_as(q)
+        .bind(business_id)
+        .bind(key)
+        .fetch_one(&mut **tx)
+        .await
+}
+
+async fn store_idempotency_response(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    key: &str,
+    response_body: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let q = r#"
+        UPDATE idempotency_keys
+        SET response_body = $3
+        WHERE business_id = $1 AND key = $2
+    "#;
+
+    sqlx::query(q)
+        .bind(business_id)
+        .bind(key)
+        .bind(response_body)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+const MAX_DESCRIPTION_LEN: usize = 500;
+const MAX_COUNTERPARTY_NAME_LEN: usize = 140;
+const MAX_COUNTERPARTY_FIELD_LEN: usize = 255;
+
+// Non-empty and at most `max` characters.
+fn text_len_within(s: &str, max: usize) -> bool {
+    !s.trim().is_empty() && s.chars().count() <= max
+}
+
+fn validate_counterparty(
+    tx_type: TransactionType,
+    counterparty: &Counterparty,
+) -> Result<(), TransactionError> {
+    if matches!(tx_type, TransactionType::Transfer) {
+        return Err(TransactionError::Validation {
+            field: "counterparty",
+            message: "counterparty is only allowed on credits and debits",
+        });
+    }
+    if !text_len_within(&counterparty.name, MAX_COUNTERPARTY_NAME_LEN) {
+        return Err(TransactionError::Validation {
+            field: "counterparty.name",
+            message: "counterparty.name must be 1-140 characters",
+        });
+    }
+    let account_identifier = counterparty.account_identifier.as_deref();
+    if account_identifier.is_some_and(|s| !text_len_within(s, MAX_COUNTERPARTY_FIELD_LEN)) {
+        return Err(TransactionError::Validation {
+            field: "counterparty.account_identifier",
+            message: "counterparty.account_identifier must be 1-255 characters",
+        });
+    }
+    let reference = counterparty.reference.as_deref();
+    if reference.is_some_and(|s| !text_len_within(s, MAX_COUNTERPARTY_FIELD_LEN)) {
+        return Err(TransactionError::Validation {
+            field: "counterparty.reference",
+            message: "counterparty.reference must be 1-255 characters",
+        });
+    }
+    Ok(())
+}
+
+// A CreateTransactionRequest that passed every check not needing the DB.
+pub(crate) struct ValidatedRequest {
+    tx_type: TransactionType,
+    source_account_id: Option<Uuid>,
+    dest_account_id: Option<Uuid>,
+    amount: i64,
+    explicit_rate: Option<FxRate>,
+    quote_id: Option<Uuid>,
+    metadata: Option<Metadata>,
+    external_reference: Option<String>,
+    description: Option<String>,
+    counterparty: Option<Counterparty>,
+}
+
+// Accounts outside `allowed_account_ids` behave as if they did not exist.
+pub(crate) fn validate_request(
+    req: &CreateTransactionRequest,
+    allowed_account_ids: Option<&[Uuid]>,
+) -> Result<ValidatedRequest, TransactionError> {
     if req.amount <= 0 {
-        return Err(TransactionError::BadRequest("amount must be > 0"));
+        return Err(TransactionError::Validation {
+            field: "amount",
+            message: "amount must be > 0",
+        });
+    }
+    if let Some(m) = &req.metadata {
+        metadata::validate(m).map_err(|message| TransactionError::Validation {
+            field: "metadata",
+            message,
+        })?;
+    }
+    if let Some(r) = &req.external_reference {
+        metadata::validate_external_reference(r).map_err(|message| {
+            TransactionError::Validation {
+                field: "external_reference",
+                message,
+            }
+        })?;
+    }
+    let description = req.description.as_deref();
+    if description.is_some_and(|d| !text_len_within(d, MAX_DESCRIPTION_LEN)) {
+        return Err(TransactionError::Validation {
+            field: "description",
+            message: "description must be 1-500 characters",
+        });
+    }
+    if let Some(c) = &req.counterparty {
+        validate_counterparty(req.tx_type, c)?;
     }
 
-    let mut tx = pool.begin().await.map_err(|_| TransactionError::Internal)?;
+    let explicit_rate = match req.fx_rate.as_deref() {
+        None => None,
+        Some(_) if !matches!(req.tx_type, TransactionType::Transfer) => {
+            return Err(TransactionError::Validation {
+                field: "fx_rate",
+                message: "fx_rate is only allowed on transfers",
+            });
+        }
+        Some(raw) => Some(FxRate::parse(raw).ok_or(TransactionError::Validation {
+            field: "fx_rate",
+            message: "fx_rate must be a positive decimal string, e.g. \"0.92\"",
+        })?),
+    };
 
-    let tx_type = req.tx_type;
-    let amount = req.amount;
+    if req.quote_id.is_some() {
+        if !matches!(req.tx_type, TransactionType::Transfer) {
+            return Err(TransactionError::Validation {
+                field: "quote_id",
+                message: "quote_id is only allowed on transfers",
+            });
+        }
+        if req.fx_rate.is_some() {
+            return Err(TransactionError::Validation {
+                field: "fx_rate",
+                message: "fx_rate cannot be combined with quote_id",
+            });
+        }
+    }
 
-    let (source_account_id, dest_account_id) = match tx_type {
+    if let Some(allowed) = allowed_account_ids {
+        let out_of_scope = [req.source_account_id, req.dest_account_id]
+            .into_iter()
+            .flatten()
+            .any(|id| !allowed.contains(&id));
+        if out_of_scope {
+            return Err(TransactionError::AccountNotFound);
+        }
+    }
+
+    let (source_account_id, dest_account_id) = match req.tx_type {
         TransactionType::Credit => {
-            if req.dest_account_id.is_none() || req.source_account_id.is_some() {
-                return Err(TransactionError::BadRequest(
-                    "credit requires dest_account_id and no source_account_id",
-                ));
+            if req.dest_account_id.is_none() {
+                return Err(TransactionError::Validation {
+                    field: "dest_account_id",
+                    message: "credit requires dest_account_id and no source_account_id",
+                });
+            }
+            if req.source_account_id.is_some() {
+                return Err(TransactionError::Validation {
+                    field: "source_account_id",
+                    message: "credit requires dest_account_id and no source_account_id",
+                });
             }
             (None, req.dest_account_id)
         }
         TransactionType::Debit => {
-            if req.source_account_id.is_none() || req.dest_account_id.is_some() {
-                return Err(TransactionError::BadRequest(
-                    "debit requires source_account_id and no dest_account_id",
-                ));
+            if req.source_account_id.is_none() {
+                return Err(TransactionError::Validation {
+                    field: "source_account_id",
+                    message: "debit requires source_account_id and no dest_account_id",
+                });
+            }
+            if req.dest_account_id.is_some() {
+                return Err(TransactionError::Validation {
+                    field: "dest_account_id",
+                    message: "debit requires source_account_id and no dest_account_id",
+                });
             }
             (req.source_account_id, None)
         }
         TransactionType::Transfer => {
-            if req.source_account_id.is_none() || req.dest_account_id.is_none() {
-                return Err(TransactionError::BadRequest(
-                    "transfer requires source_account_id and dest_account_id",
-                ));
+            if req.source_account_id.is_none() {
+                return Err(TransactionError::Validation {
+                    field: "source_account_id",
+                    message: "transfer requires source_account_id and dest_account_id",
+                });
+            }
+            if req.dest_account_id.is_none() {
+                return Err(TransactionError::Validation {
+                    field: "dest_account_id",
+                    message: "transfer requires source_account_id and dest_account_id",
+                });
             }
             let s = req.source_account_id.unwrap();
             let d = req.dest_account_id.unwrap();
             if s == d {
-                return Err(TransactionError::BadRequest(
-                    "transfer requires distinct source and destination accounts",
-                ));
+                return Err(TransactionError::Validation {
+                    field: "dest_account_id",
+                    message: "transfer requires distinct source and destination accounts",
+                });
             }
             (Some(s), Some(d))
         }
     };
 
-    match tx_type {
+    Ok(ValidatedRequest {
+        tx_type: req.tx_type,
+        source_account_id,
+        dest_account_id,
+        amount: req.amount,
+        explicit_rate,
+        quote_id: req.quote_id,
+        metadata: req.metadata.clone(),
+        external_reference: req.external_reference.clone(),
+        description: req.description.clone(),
+        counterparty: req.counterparty.clone(),
+    })
+}
+
+// Locks the request's accounts, checks balances and records the transaction inside `tx`.
+// Locking an account already locked by the same DB transaction (batches) does not block.
+async fn execute_request(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    v: &ValidatedRequest,
+) -> Result<Transaction, TransactionError> {
+    let (amount, source_account_id, dest_account_id) =
+        (v.amount, v.source_account_id, v.dest_account_id);
+
+    let out = match v.tx_type {
         TransactionType::Credit => {
             let dest_id = dest_account_id.unwrap();
 
             // 1) Lock row
-            let dest = lock_account_for_update(&mut tx, business_id, dest_id)
+            let dest = lock_account_for_update(tx, business_id, dest_id)
                 .await
-                .map_err(|_| TransactionError::Internal)?;
-
-            let (_, _currency, dest_balance) = dest.ok_or(TransactionError::NotFound)?;
+                .map_err(|_| TransactionError::Internal)?
+                .ok_or(TransactionError::AccountNotFound)?;
+            dest.ensure_can_credit()?;
 
-            // 2) Balance update + insert in same DB transaction
-            let new_balance = dest_balance
-                .checked_add(amount)
-                .ok_or(TransactionError::Internal)?;
+            // 2) Insert + postings (which update the cached balance) in same DB transaction
+            record_transaction(
+                tx,
+                business_id,
+                &NewTransaction {
+                    tx_type: v.tx_type,
+                    source_account_id: None,
+                    dest_account_id: Some(dest_id),
+                    amount,
+                    currency: &dest.currency,
+                    fx: None,
+                    reversal_of: None,
+                    metadata: v.metadata.as_ref(),
+                    external_reference: v.external_reference.as_deref(),
+                    description: v.description.as_deref(),
+                    counterparty: v.counterparty.as_ref(),
+                },
+            )
+            .await?
+        }
+        TransactionType::Debit => {
+            let source_id = source_account_id.unwrap();
 
-            update_balance(&mut tx, dest_id, new_balance)
+            // 1) Lock row
+            let source = lock_account_for_update(tx, business_id, source_id)
                 .await
-                .map_err(|_| TransactionError::Internal)?;
+                .map_err(|_| TransactionError::Internal)?
+                .ok_or(TransactionError::AccountNotFound)?;
+            source.ensure_can_debit()?;
 
-            let (id, created_at) = insert_transaction_row(
-                &mut tx,
+            // 2) Balance and limit checks (after lock)
+            if source.available < amount {
+                return Err(TransactionError::InsufficientFunds);
+            }
+            check_outflow_limits(tx, &source, amount).await?;
+
+            // 3) Insert + postings (which update the cached balance) in same DB transaction
+            record_transaction(
+                tx,
                 business_id,
-                tx_type,
-                None,
-                Some(dest_id),
-                amount,
+                &NewTransaction {
+                    tx_type: v.tx_type,
+                    source_account_id: Some(source_id),
+                    dest_account_id: None,
+                    amount,
+                    currency: &source.currency,
+                    fx: None,
+                    reversal_of: None,
+                    metadata: v.metadata.as_ref(),
+                    external_reference: v.external_reference.as_deref(),
+                    description: v.description.as_deref(),
+                    counterparty: v.counterparty.as_ref(),
+                },
             )
-            .await
-            .map_err(|_| TransactionError::Internal)?;
+            .await?
+        }
+        TransactionType::Transfer => {
+            let source_id = source_account_id.unwrap();
+            let dest_id = dest_account_id.unwrap();
 
-            tx.commit().await.map_err(|_| TransactionError::Internal)?;
+            let (source, dest) = lock_transfer_accounts(tx, business_id, source_id, dest_id).await?;
 
-            let out = Transaction {
-                id,
-                business_id,
-                tx_type,
-                source_account_id: None,
-                dest_account_id: Some(dest_id),
-                amount,
-                created_at,
+            let fx = if let Some(quote_id) = v.quote_id {
+                let conversion =
+                    consume_quote(tx, business_id, quote_id, source_id, dest_id, amount).await?;
+                Some(conversion)
+            } else if source.currency.trim() != dest.currency.trim() {
+                let conversion = convert_for_transfer(
+                    tx,
+                    business_id,
+                    &source.currency,
+                    &dest.currency,
+                    v.explicit_rate,
+                    amount,
+                )
+                .await?;
+                Some(conversion)
+            } else if v.explicit_rate.is_some() {
+                return Err(TransactionError::Validation {
+                    field: "fx_rate",
+                    message: "fx_rate is only allowed when the accounts' currencies differ",
+                });
+            } else {
+                None
             };
 
-            enqueue_transaction_created_events_best_effort(pool, business_id, &out).await;
+            // Balance and limit checks (after locks)
+            if source.available < amount {
+                return Err(TransactionError::InsufficientFunds);
+            }
+            check_outflow_limits(tx, &source, amount).await?;
+
+            let out = record_transaction(
+                tx,
+                business_id,
+                &NewTransaction {
+                    tx_type: v.tx_type,
+                    source_account_id: Some(source_id),
+                    dest_account_id: Some(dest_id),
+                    amount,
+                    currency: &source.currency,
+                    fx: fx.as_ref(),
+                    reversal_of: None,
+                    metadata: v.metadata.as_ref(),
+                    external_reference: v.external_reference.as_deref(),
+                    description: v.description.as_deref(),
+                    counterparty: v.counterparty.as_ref(),
+                },
+            )
+            .await?;
+
+            if let Some(quote_id) = v.quote_id {
+                db::mark_fx_quote_used(&mut **tx, quote_id, out.id)
+                    .await
+                    .map_err(|_| TransactionError::Internal)?;
+            }
 
-            Ok(out)
+            out
         }
-        TransactionType::Debit => {
-            let source_id = source_account_id.unwrap();
+    };
 
-            // 1) Lock row
-            let source = lock_account_for_update(&mut tx, business_id, source_id)
-                .await
-                .map_err(|_| TransactionError::Internal)?;
+    Ok(out)
+}
 
-            let (_, _currency, source_balance) = source.ok_or(TransactionError::NotFound)?;
+// allowed_account_ids: account allowlist of the calling API key (None = unrestricted).
+pub async fn create_transaction(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    idempotency_key: Option<&str>,
+    req: CreateTransactionRequest,
+) -> Result<CreateTransactionOutcome, TransactionError> {
+    let validated = validate_request(&req, allowed_account_ids)?;
 
-            // 2) Balance check (after lock)
-            if source_balance < amount {
-                return Err(TransactionError::InsufficientFunds);
-            }
+    if let Some(key) = idempotency_key {
+        validate_idempotency_key(key)?;
+    }
 
-            // 3) Balance update + insert in same DB transaction
-            let new_balance = source_balance
-                .checked_sub(amount)
-                .ok_or(TransactionError::Internal)?;
+    let mut tx = pool.begin().await.map_err(|_| TransactionError::Internal)?;
+
+    if let Some(key) = idempotency_key {
+        let request_hash = request_fingerprint(&req)?;
 
-            update_balance(&mut tx, source_id, new_balance)
+        // Claiming the key first serializes concurrent requests with the same key:
+        // the second insert waits on the unique index until the first one commits or rolls back.
+        let claimed = claim_idempotency_key(&mut tx, business_id, key, &request_hash)
+            .await
+            .map_err(|_| TransactionError::Internal)?;
+
+        if !claimed {
+            let (stored_hash, stored_body) = load_idempotency_key(&mut tx, business_id, key)
                 .await
                 .map_err(|_| TransactionError::Internal)?;
 
-            let (id, created_at) = insert_transaction_row(
-                &mut tx,
-                business_id,
-                tx_type,
-                Some(source_id),
-                None,
-                amount,
-            )
+            if stored_hash != request_hash {
+                return Err(TransactionError::IdempotencyKeyReused);
+            }
+
+            let body = stored_body.ok_or(TransactionError::Internal)?;
+            return Ok(CreateTransactionOutcome::Replayed(body));
+        }
+    }
+
+    let out = execute_request(&mut tx, business_id, &validated).await?;
+
+    if let Some(key) = idempotency_key {
+        let response_body = serde_json::to_value(TransactionResponse::from(out.clone()))
+            .map_err(|_| TransactionError::Internal)?;
+
+        store_idempotency_response(&mut tx, business_id, key, &response_body)
             .await
             .map_err(|_| TransactionError::Internal)?;
+    }
 
-            tx.commit().await.map_err(|_| TransactionError::Internal)?;
+    // Outbox rows are written in the same DB transaction as the balance update,
+    // so a committed transaction always has its webhook events queued.
+    enqueue_transaction_created_events(&mut tx, business_id, &out)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
 
-            let out = Transaction {
-                id,
-                business_id,
-                tx_type,
-                source_account_id: Some(source_id),
-                dest_account_id: None,
-                amount,
-                created_at,
-            };
+    tx.commit().await.map_err(|_| TransactionError::Internal)?;
 
-            enqueue_transaction_created_events_best_effort(pool, business_id, &out).await;
+    Ok(CreateTransactionOutcome::Created(Box::new(out)))
+}
 
-            Ok(out)
-        }
-        TransactionType::Transfer => {
-            let source_id = source_account_id.unwrap();
-            let dest_id = dest_account_id.unwrap();
+/// Creates the transaction of one occurrence of a schedule inside the scheduler's DB
+/// transaction, which holds the schedule row lock and records the run, so the transfer and its
+/// run commit or roll back together. A rejected request is rolled back to a savepoint and
+/// returned as the inner error, leaving `tx` usable for recording the failed run.
+pub(crate) async fn create_scheduled_transaction(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    req: &CreateTransactionRequest,
+) -> Result<Result<Transaction, TransactionError>, TransactionError> {
+    match validate_request(req, None) {
+        Ok(v) => execute_in_savepoint(tx, business_id, &v).await,
+        Err(err) => Ok(Err(err)),
+    }
+}
 
-            // Deterministic lock order to avoid deadlocks.
-            let (first, second) = order_uuids(source_id, dest_id);
+pub const MAX_BATCH_SIZE: usize = 500;
 
-            let first_row = lock_account_for_update(&mut tx, business_id, first)
-                .await
-                .map_err(|_| TransactionError::Internal)?;
-            let second_row = lock_account_for_update(&mut tx, business_id, second)
-                .await
-                .map_err(|_| TransactionError::Internal)?;
+#[derive(Debug)]
+pub struct BatchOutcome {
+    // Whether anything was committed (all_or_nothing: every item succeeded; best_effort: at
+    // least one did).
+    pub committed: bool,
+    // One per request, in order.
+    pub results: Vec<Result<Transaction, TransactionError>>,
+}
 
-            let (first_id, first_currency, first_balance) =
-                first_row.ok_or(TransactionError::NotFound)?;
-            let second_row = second_row.ok_or(TransactionError::NotFound)?;
-            let second_currency = second_row.1;
-            let second_balance = second_row.2;
+// Runs one batch item in a savepoint, so a failed item leaves no trace and the rest of the
+// batch can continue in the same DB transaction.
+async fn execute_in_savepoint(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    v: &ValidatedRequest,
+) -> Result<Result<Transaction, TransactionError>, TransactionError> {
+    let mut savepoint = tx.begin().await.map_err(|_| TransactionError::Internal)?;
 
-            // Re-associate balances to source/dest
-            let (source_currency, source_balance, dest_currency, dest_balance) = if first_id == source_id {
-                (first_currency, first_balance, second_currency, second_balance)
-            } else {
-                (second_currency, second_balance, first_currency, first_balance)
-            };
+    let result = match execute_request(&mut savepoint, business_id, v).await {
+        Ok(out) => enqueue_transaction_created_events(&mut savepoint, business_id, &out)
+            .await
+            .map(|_| out)
+            .map_err(|_| TransactionError::Internal),
+        Err(err) => Err(err),
+    };
 
-            if source_currency.trim() != dest_currency.trim() {
-                return Err(TransactionError::BadRequest(
-                    "transfer requires source and destination accounts to have same currency",
-                ));
-            }
+    match &result {
+        Ok(_) => savepoint.commit().await,
+        Err(_) => savepoint.rollback().await,
+    }
+    .map_err(|_| TransactionError::Internal)?;
 
-            // Balance check (after locks)
-            if source_balance < amount {
-                return Err(TransactionError::InsufficientFunds);
-            }
+    Ok(result)
+}
 
-            let new_source_balance = source_balance
-                .checked_sub(amount)
-                .ok_or(TransactionError::Internal)?;
-            let new_dest_balance = dest_balance
-                .checked_add(amount)
-                .ok_or(TransactionError::Internal)?;
+/// Executes up to `MAX_BATCH_SIZE` requests and reports each item's outcome. Items run in
+/// order, so later items see the balances left by earlier ones.
+///
+/// `all_or_nothing` runs the whole batch in one DB transaction; `best_effort` commits each
+/// item on its own. Idempotency-Key is not supported for batches.
+pub async fn create_transaction_batch(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    mode: BatchMode,
+    reqs: &[CreateTransactionRequest],
+) -> Result<BatchOutcome, TransactionError> {
+    if reqs.is_empty() || reqs.len() > MAX_BATCH_SIZE {
+        return Err(TransactionError::Validation {
+            field: "transactions",
+            message: "transactions must contain between 1 and 500 items",
+        });
+    }
 
-            update_balance(&mut tx, source_id, new_source_balance)
-                .await
-                .map_err(|_| TransactionError::Internal)?;
-            update_balance(&mut tx, dest_id, new_dest_balance)
-                .await
-                .map_err(|_| TransactionError::Internal)?;
+    let validated: Vec<Result<ValidatedRequest, TransactionError>> = reqs
+        .iter()
+        .map(|req| validate_request(req, allowed_account_ids))
+        .collect();
 
-            let (id, created_at) = insert_transaction_row(
-                &mut tx,
-                business_id,
-                tx_type,
-                Some(source_id),
-                Some(dest_id),
-                amount,
-            )
+    if mode == BatchMode::BestEffort {
+        return Ok(execute_items_separately(pool, business_id, validated).await);
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| TransactionError::Internal)?;
+
+    // Every account of the batch is locked up front in the global order, so batches and single
+    // transfers touching the same accounts cannot deadlock each other. Missing accounts are
+    // skipped here and reported by the item that references them.
+    let account_ids = lock_order(
+        validated
+            .iter()
+            .flatten()
+            .flat_map(|v| [v.source_account_id, v.dest_account_id])
+            .flatten(),
+    );
+    for account_id in account_ids {
+        lock_account_for_update(&mut tx, business_id, account_id)
             .await
             .map_err(|_| TransactionError::Internal)?;
+    }
 
-            tx.commit().await.map_err(|_| TransactionError::Internal)?;
+    let mut results = Vec::with_capacity(reqs.len());
+    for item in validated {
+        let result = match item {
+            Ok(v) => execute_in_savepoint(&mut tx, business_id, &v).await?,
+            Err(err) => Err(err),
+        };
+        results.push(result);
+    }
 
-            let out = Transaction {
-                id,
-                business_id,
-                tx_type,
-                source_account_id: Some(source_id),
-                dest_account_id: Some(dest_id),
-                amount,
-                created_at,
+    let committed = results.iter().all(|r| r.is_ok());
+    if committed {
+        tx.commit().await.map_err(|_| TransactionError::Internal)?;
+    } else {
+        tx.rollback().await.map_err(|_| TransactionError::Internal)?;
+    }
+
+    Ok(BatchOutcome { committed, results })
+}
+
+// best_effort: every item runs and commits in its own DB transaction, so a success is durable
+// right away and only that item's accounts are locked while it runs. An internal error only
+// fails its own item, since the items before it are already committed.
+async fn execute_items_separately(
+    pool: &PgPool,
+    business_id: Uuid,
+    validated: Vec<Result<ValidatedRequest, TransactionError>>,
+) -> BatchOutcome {
+    let mut results = Vec::with_capacity(validated.len());
+    for item in validated {
+        let result = match item {
+            Ok(v) => execute_and_commit(pool, business_id, &v).await,
+            Err(err) => Err(err),
+        };
+        results.push(result);
+    }
+
+    let committed = results.iter().any(|r| r.is_ok());
+    BatchOutcome { committed, results }
+}
+
+async fn execute_and_commit(
+    pool: &PgPool,
+    business_id: Uuid,
+    v: &ValidatedRequest,
+) -> Result<Transaction, TransactionError> {
+    let mut tx = pool.begin().await.map_err(|_| TransactionError::Internal)?;
+
+    let out = execute_request(&mut tx, business_id, v).await?;
+    enqueue_transaction_created_events(&mut tx, business_id, &out)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+
+    tx.commit().await.map_err(|_| TransactionError::Internal)?;
+
+    Ok(out)
+}
+
+// Destination-side amount of the first `reversed` units of an FX transfer's `amount`, at the
+// transfer's own ratio. Reversal legs take differences of this, so partial reversals add up to
+// exactly `dest_amount`.
+fn dest_share(fx: &FxConversion, amount: i64, reversed: i64) -> Option<i64> {
+    let numerator = (fx.dest_amount as i128).checked_mul(reversed as i128)?;
+    let denominator = amount as i128;
+
+    let mut share = numerator / denominator;
+    if (numerator % denominator) * 2 >= denominator {
+        share += 1;
+    }
+
+    i64::try_from(share).ok()
+}
+
+// Destination-side amount of reversing `reversing` more units of an FX transfer of which
+// `reversed` units have already been reversed.
+fn reversal_dest_part(
+    fx: &FxConversion,
+    amount: i64,
+    reversed: i64,
+    reversing: i64,
+) -> Result<i64, TransactionError> {
+    let before = dest_share(fx, amount, reversed);
+    let after = dest_share(fx, amount, reversed + reversing);
+    let dest_part = after
+        .zip(before)
+        .map(|(after, before)| after - before)
+        .ok_or(TransactionError::Internal)?;
+    if dest_part == 0 {
+        return Err(TransactionError::Validation {
+            field: "amount",
+            message: "amount is too small to reverse at the original rate",
+        });
+    }
+
+    Ok(dest_part)
+}
+
+/// Reverses `req.amount` (default: everything not yet reversed) of transaction `id` with a
+/// linked compensating transaction that moves the money back: a credit is undone by a debit,
+/// a debit by a credit, and a transfer by a transfer in the opposite direction (at the
+/// original rate for cross-currency transfers).
+pub async fn reverse_transaction(
+    pool: &PgPool,
+    business_id: Uuid,
+    allowed_account_ids: Option<&[Uuid]>,
+    id: Uuid,
+    req: ReverseTransactionRequest,
+) -> Result<Transaction, TransactionError> {
+    if req.amount.is_some_and(|a| a <= 0) {
+        return Err(TransactionError::Validation {
+            field: "amount",
+            message: "amount must be > 0",
+        });
+    }
+
+    let mut tx = pool.begin().await.map_err(|_| TransactionError::Internal)?;
+
+    // Locked before the accounts, so concurrent reversals of one transaction queue up here
+    // and each sees the previous one's reversed_amount.
+    let original = db::lock_transaction_for_update(&mut *tx, business_id, id)
+        .await
+        .map_err(|_| TransactionError::Internal)?
+        .ok_or(TransactionError::TransactionNotFound)?;
+
+    if let Some(allowed) = allowed_account_ids {
+        let all_allowed = [original.source_account_id, original.dest_account_id]
+            .into_iter()
+            .flatten()
+            .all(|account_id| allowed.contains(&account_id));
+        if !original.touches_any(allowed) {
+            return Err(TransactionError::TransactionNotFound);
+        }
+        if !all_allowed {
+            return Err(TransactionError::AccountNotFound);
+        }
+    }
+
+    if original.reversal_of.is_some() {
+        return Err(TransactionError::Validation {
+            field: "id",
+            message: "a reversal cannot itself be reversed",
+        });
+    }
+
+    let remaining = original.amount - original.reversed_amount;
+    if remaining == 0 {
+        return Err(TransactionError::AlreadyReversed);
+    }
+    let amount = req.amount.unwrap_or(remaining);
+    if amount > remaining {
+        return Err(TransactionError::Validation {
+            field: "amount",
+            message: "amount exceeds the part of the transaction not yet reversed",
+        });
+    }
+
+    // The compensating movement swaps the original's sides.
+    let (tx_type, source_id, dest_id) = match original.tx_type {
+        TransactionType::Credit => (TransactionType::Debit, original.dest_account_id, None),
+        TransactionType::Debit => (TransactionType::Credit, None, original.source_account_id),
+        TransactionType::Transfer => (
+            TransactionType::Transfer,
+            original.dest_account_id,
+            original.source_account_id,
+        ),
+    };
+
+    // The account the reversal debits, if any.
+    let source = match (source_id, dest_id) {
+        (Some(source_id), Some(dest_id)) => {
+            let (source, _) =
+                lock_transfer_accounts(&mut tx, business_id, source_id, dest_id).await?;
+            Some(source)
+        }
+        (Some(account_id), None) | (None, Some(account_id)) => {
+            let account = lock_account_for_update(&mut tx, business_id, account_id)
+                .await
+                .map_err(|_| TransactionError::Internal)?
+                .ok_or(TransactionError::AccountNotFound)?;
+            if source_id.is_some() {
+                account.ensure_can_debit()?;
+                Some(account)
+            } else {
+                account.ensure_can_credit()?;
+                None
+            }
+        }
+        (None, None) => return Err(TransactionError::Internal),
+    };
+
+    // An FX transfer is undone in the destination currency: the reversal debits the original
+    // destination its share of dest_amount and credits `amount` back in the source currency.
+    let reversal_fx = match &original.fx {
+        None => None,
+        Some(fx) => {
+            let dest_part =
+                reversal_dest_part(fx, original.amount, original.reversed_amount, amount)?;
+
+            let rate = FxRate::parse(&fx.rate)
+                .and_then(FxRate::inverse)
+                .ok_or(TransactionError::Internal)?;
+            let conversion = FxConversion {
+                rate: rate.to_string(),
+                dest_amount: amount,
+                dest_currency: original.currency.trim().to_string(),
             };
+            Some((dest_part, fx.dest_currency.clone(), conversion))
+        }
+    };
 
-            enqueue_transaction_created_events_best_effort(pool, business_id, &out).await;
+    let (reversal_amount, reversal_currency) = match &reversal_fx {
+        Some((dest_part, dest_currency, _)) => (*dest_part, dest_currency.clone()),
+        None => (amount, original.currency.clone()),
+    };
 
-            Ok(out)
+    if let Some(source) = &source {
+        if source.available < reversal_amount {
+            return Err(TransactionError::InsufficientFunds);
         }
+        check_outflow_limits(&mut tx, source, reversal_amount).await?;
     }
+
+    let reversal = record_transaction(
+        &mut tx,
+        business_id,
+        &NewTransaction {
+            tx_type,
+            source_account_id: source_id,
+            dest_account_id: dest_id,
+            amount: reversal_amount,
+            currency: &reversal_currency,
+            fx: reversal_fx.as_ref().map(|(_, _, conversion)| conversion),
+            reversal_of: Some(original.id),
+            metadata: None,
+            external_reference: None,
+            description: None,
+            // The money goes back to (or comes back from) the same external party.
+            counterparty: original.counterparty.as_ref(),
+        },
+    )
+    .await?;
+
+    // The reversed_amount_within_amount check backs up the `remaining` check above.
+    let reversed_amount = db::add_reversed_amount(&mut *tx, original.id, amount)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+
+    enqueue_transaction_reversed_events(&mut tx, business_id, &reversal, amount, reversed_amount)
+        .await
+        .map_err(|_| TransactionError::Internal)?;
+
+    tx.commit().await.map_err(|_| TransactionError::Internal)?;
+
+    Ok(reversal)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fx(dest_amount: i64) -> FxConversion {
+        FxConversion {
+            rate: "0.92".to_string(),
+            dest_amount,
+            dest_currency: "EUR".to_string(),
+        }
+    }
+
+    // Reverses `amount` in the given parts and returns each part's destination amount.
+    fn reverse_in_parts(fx: &FxConversion, amount: i64, parts: &[i64]) -> Vec<i64> {
+        let mut reversed = 0;
+        parts
+            .iter()
+            .map(|&part| {
+                let dest_part = reversal_dest_part(fx, amount, reversed, part).unwrap();
+                reversed += part;
+                dest_part
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dest_share_is_proportional_and_rounds_half_up() {
+        let fx = fx(920);
+        assert_eq!(dest_share(&fx, 1000, 0), Some(0));
+        assert_eq!(dest_share(&fx, 1000, 500), Some(460));
+        assert_eq!(dest_share(&fx, 1000, 1000), Some(920));
+        // 920 * 1 / 1000 = 0.92 and 920 * 3 / 1000 = 2.76.
+        assert_eq!(dest_share(&fx, 1000, 1), Some(1));
+        assert_eq!(dest_share(&fx, 1000, 3), Some(3));
+        // 3 * 1 / 2 = 1.5.
+        assert_eq!(dest_share(&self::fx(3), 2, 1), Some(2));
+    }
+
+    #[test]
+    fn partial_reversals_add_up_to_dest_amount() {
+        // Each third of 1000 destination units is 333.33; the rounding error is not lost.
+        let parts = reverse_in_parts(&fx(1000), 3, &[1, 1, 1]);
+        assert_eq!(parts, vec![333, 334, 333]);
 
+        for (dest_amount, amount, parts) in [
+            (920, 1000, vec![100; 10]),
+            (997, 7, vec![1; 7]),
+            (1505, 1000, vec![333, 333, 334]),
+            (2, 3, vec![2, 1]),
+            (123_456, 999, vec![1, 998]),
+        ] {
+            let dest_parts = reverse_in_parts(&fx(dest_amount), amount, &parts);
+            assert_eq!(dest_parts.iter().sum::<i64>(), dest_amount, "{dest_parts:?}");
+        }
+    }
+
+    #[test]
+    fn reversal_too_small_for_the_destination_is_rejected() {
+        // 1 of 1000 source units is worth 0.05 of 50 destination units.
+        let err = reversal_dest_part(&fx(50), 1000, 0, 1).unwrap_err();
+        assert!(matches!(err, TransactionError::Validation { field: "amount", .. }));
+
+        // Once earlier parts have rounded down, the same amount picks up the remainder.
+        assert_eq!(reversal_dest_part(&fx(50), 1000, 9, 1).unwrap(), 1);
+    }
+}
@@ -1,23 +1,43 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
 use serde_json::json;
-use sqlx::PgPool;
+use sha2::Sha256;
+use sqlx::{Postgres, Transaction as SqlxTransaction};
 use uuid::Uuid;
 
-use crate::{db, models::transaction::Transaction};
+use crate::{
+    db,
+    models::{
+        account::{Account, AccountStatus},
+        fx::FxConversionResponse,
+        scheduled_transaction::{ScheduledTransaction, ScheduledTransactionRun},
+        transaction::Transaction,
+    },
+};
 
 #[derive(Debug)]
 pub enum WebhookError {
-    BadRequest(&'static str),
+    Validation {
+        field: &'static str,
+        message: &'static str,
+    },
     Internal,
 }
 
 fn validate_url(url: &str) -> Result<(), WebhookError> {
     let u = url.trim();
     if u.is_empty() {
-        return Err(WebhookError::BadRequest("url is required"));
+        return Err(WebhookError::Validation {
+            field: "url",
+            message: "url is required",
+        });
     }
     if !(u.starts_with("http://") || u.starts_with("https://")) {
-        return Err(WebhookError::BadRequest("url must start with http:// or https://"));
+        return Err(WebhookError::Validation {
+            field: "url",
+            message: "url must start with http:// or https://",
+        });
     }
     Ok(())
 }
@@ -27,38 +47,292 @@ pub fn validate_create_endpoint(url: &str) -> Result<(), WebhookError> {
     Ok(())
 }
 
-pub async fn enqueue_transaction_created_events_best_effort(
-    pool: &PgPool,
+/// Header carrying the delivery signature, formatted as `t=<unix seconds>,v1=<hex hmac>`.
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Default replay window for `verify_webhook_signature`.
+pub const DEFAULT_SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Header is missing `t=` or `v1=`, or a value cannot be parsed.
+    Malformed,
+    /// Timestamp is further from `now` than the allowed tolerance.
+    TimestampOutOfTolerance,
+    /// No `v1=` signature matches the body.
+    Mismatch,
+}
+
+pub fn generate_endpoint_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+fn signature_mac(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Builds the `X-Webhook-Signature` header value: HMAC-SHA256 over `"{timestamp}.{body}"`.
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let sig = signature_mac(secret, timestamp, body).finalize().into_bytes();
+    format!("t={timestamp},v1={}", hex::encode(sig))
+}
+
+/// Verifies a received `X-Webhook-Signature` header against the raw request body.
+///
+/// Rejects deliveries whose timestamp is more than `tolerance_secs` away from now,
+/// which bounds how long a captured request can be replayed.
+pub fn verify_webhook_signature(
+    secret: &str,
+    signature_header: &str,
+    body: &[u8],
+    tolerance_secs: i64,
+) -> Result<(), SignatureError> {
+    verify_webhook_signature_at(secret, signature_header, body, tolerance_secs, Utc::now())
+}
+
+/// Same as `verify_webhook_signature`, with an explicit "now".
+pub fn verify_webhook_signature_at(
+    secret: &str,
+    signature_header: &str,
+    body: &[u8],
+    tolerance_secs: i64,
+    now: DateTime<Utc>,
+) -> Result<(), SignatureError> {
+    let mut timestamp: Option<i64> = None;
+    let mut signatures: Vec<Vec<u8>> = Vec::new();
+
+    for part in signature_header.split(',') {
+        let (k, v) = part.trim().split_once('=').ok_or(SignatureError::Malformed)?;
+        match k {
+            "t" => timestamp = Some(v.parse().map_err(|_| SignatureError::Malformed)?),
+            "v1" => signatures.push(hex::decode(v).map_err(|_| SignatureError::Malformed)?),
+            // Unknown schemes are ignored so new ones can be added without breaking receivers.
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or(SignatureError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+
+    if (now.timestamp() - timestamp).abs() > tolerance_secs {
+        return Err(SignatureError::TimestampOutOfTolerance);
+    }
+
+    let matched = signatures.iter().any(|sig| {
+        signature_mac(secret, timestamp, body)
+            .verify_slice(sig)
+            .is_ok()
+    });
+
+    if matched {
+        Ok(())
+    } else {
+        Err(SignatureError::Mismatch)
+    }
+}
+
+fn transaction_event_data(transaction: &Transaction) -> serde_json::Value {
+    let mut data = json!({
+        "transaction_id": transaction.id,
+        "type": transaction.tx_type,
+        "source_account_id": transaction.source_account_id,
+        "dest_account_id": transaction.dest_account_id,
+        "amount": transaction.amount,
+        "currency": transaction.currency.trim(),
+        "metadata": transaction.metadata,
+        "external_reference": transaction.external_reference,
+        "description": transaction.description,
+        "counterparty": transaction.counterparty,
+        "created_at": transaction.created_at,
+    });
+    if let Some(fx) = &transaction.fx {
+        data["fx"] = json!(FxConversionResponse::from(fx.clone()));
+    }
+    data
+}
+
+async fn enqueue_event(
+    tx: &mut SqlxTransaction<'_, Postgres>,
     business_id: Uuid,
-    tx: &Transaction,
-) {
+    event_type: &str,
+    transaction_id: Option<Uuid>,
+    data: serde_json::Value,
+) -> Result<(), WebhookError> {
     let payload = json!({
-        "event_type": "transaction.created",
+        "event_type": event_type,
         "timestamp": Utc::now(),
-        "data": {
-            "transaction_id": tx.id,
-            "type": tx.tx_type,
-            "source_account_id": tx.source_account_id,
-            "dest_account_id": tx.dest_account_id,
-            "amount": tx.amount,
-            "created_at": tx.created_at,
-        }
+        "data": data,
     });
 
-    let payload_json = match serde_json::to_string(&payload) {
-        Ok(s) => s,
-        Err(_) => {
-            eprintln!("failed to serialize webhook payload");
-            return;
-        }
-    };
+    let payload_json = serde_json::to_string(&payload).map_err(|_| WebhookError::Internal)?;
 
-    match db::enqueue_webhook_events_for_transaction(pool, business_id, tx.id, &payload_json).await {
-        Ok(_count) => {}
-        Err(err) => {
-            eprintln!("failed to enqueue webhook events: {err}");
-        }
-    }
+    db::enqueue_webhook_events(
+        &mut **tx,
+        business_id,
+        transaction_id,
+        &payload_json,
+    )
+    .await
+    .map_err(|err| {
+        eprintln!("failed to enqueue webhook events: {err}");
+        WebhookError::Internal
+    })?;
+
+    Ok(())
+}
+
+pub async fn enqueue_transaction_created_events(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    transaction: &Transaction,
+) -> Result<(), WebhookError> {
+    let data = transaction_event_data(transaction);
+    enqueue_event(tx, business_id, "transaction.created", Some(transaction.id), data).await
+}
+
+// `data` describes the compensating transaction, plus how much of the original it reversed
+// (`original_amount_reversed`) and the original's running total (`original_reversed_amount`),
+// both in the original's `amount` units.
+pub async fn enqueue_transaction_reversed_events(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    reversal: &Transaction,
+    original_amount_reversed: i64,
+    original_reversed_amount: i64,
+) -> Result<(), WebhookError> {
+    let mut data = transaction_event_data(reversal);
+    data["reversal_of"] = json!(reversal.reversal_of);
+    data["original_amount_reversed"] = json!(original_amount_reversed);
+    data["original_reversed_amount"] = json!(original_reversed_amount);
+
+    enqueue_event(tx, business_id, "transaction.reversed", Some(reversal.id), data).await
 }
 
+// A scheduled run whose transaction request was rejected (e.g. insufficient funds); `error`
+// is the error body the request failed with.
+pub async fn enqueue_scheduled_transaction_failed_events(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    schedule: &ScheduledTransaction,
+    run: &ScheduledTransactionRun,
+) -> Result<(), WebhookError> {
+    let data = json!({
+        "scheduled_transaction_id": schedule.id,
+        "run_id": run.id,
+        "occurrence": run.occurrence,
+        "scheduled_for": run.scheduled_for,
+        "type": schedule.tx_type,
+        "source_account_id": schedule.source_account_id,
+        "dest_account_id": schedule.dest_account_id,
+        "amount": schedule.amount,
+        "error": run.error,
+    });
+
+    enqueue_event(tx, business_id, "scheduled_transaction.failed", None, data).await
+}
+
+pub async fn enqueue_account_status_changed_events(
+    tx: &mut SqlxTransaction<'_, Postgres>,
+    business_id: Uuid,
+    account: &Account,
+    previous_status: AccountStatus,
+) -> Result<(), WebhookError> {
+    let data = json!({
+        "account_id": account.id,
+        "previous_status": previous_status,
+        "status": account.status,
+        "changed_at": account.status_changed_at,
+        "metadata": account.metadata,
+        "external_reference": account.external_reference,
+    });
+
+    enqueue_event(tx, business_id, "account.status_changed", None, data).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+    const BODY: &[u8] = br#"{"id":"evt_1"}"#;
+    const T: i64 = 1_700_000_000;
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
 
+    fn verify(header: &str, body: &[u8], now: i64) -> Result<(), SignatureError> {
+        verify_webhook_signature_at(SECRET, header, body, DEFAULT_SIGNATURE_TOLERANCE_SECS, at(now))
+    }
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        assert_eq!(
+            sign_webhook_payload(SECRET, T, BODY),
+            "t=1700000000,\
+             v1=c89214b5b5da833daed6f0b8c5bb6bd58cea9022bd80ccc78230f3942d632925"
+        );
+    }
+
+    #[test]
+    fn accepts_own_signature_within_tolerance() {
+        let header = sign_webhook_payload(SECRET, T, BODY);
+        assert_eq!(verify(&header, BODY, T), Ok(()));
+        assert_eq!(verify(&header, BODY, T + DEFAULT_SIGNATURE_TOLERANCE_SECS), Ok(()));
+        assert_eq!(verify(&header, BODY, T - DEFAULT_SIGNATURE_TOLERANCE_SECS), Ok(()));
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_tolerance() {
+        let header = sign_webhook_payload(SECRET, T, BODY);
+        let late = T + DEFAULT_SIGNATURE_TOLERANCE_SECS + 1;
+        let early = T - DEFAULT_SIGNATURE_TOLERANCE_SECS - 1;
+        assert_eq!(verify(&header, BODY, late), Err(SignatureError::TimestampOutOfTolerance));
+        assert_eq!(verify(&header, BODY, early), Err(SignatureError::TimestampOutOfTolerance));
+    }
+
+    #[test]
+    fn rejects_tampered_body_and_timestamp() {
+        let header = sign_webhook_payload(SECRET, T, BODY);
+        let tampered = br#"{"id":"evt_2"}"#;
+        assert_eq!(verify(&header, tampered, T), Err(SignatureError::Mismatch));
+
+        // Moving the timestamp forward to pass the tolerance check breaks the signature.
+        let v1 = header.split_once(",v1=").unwrap().1;
+        let replayed = format!("t={},v1={v1}", T + 1000);
+        assert_eq!(verify(&replayed, BODY, T + 1000), Err(SignatureError::Mismatch));
+    }
+
+    #[test]
+    fn rejects_wrong_secret() {
+        let header = sign_webhook_payload("whsec_other", T, BODY);
+        assert_eq!(verify(&header, BODY, T), Err(SignatureError::Mismatch));
+    }
+
+    #[test]
+    fn accepts_any_matching_v1_and_ignores_unknown_schemes() {
+        let valid = sign_webhook_payload(SECRET, T, BODY);
+        let v1 = valid.split_once(",v1=").unwrap().1;
+        let header = format!("t={T},v0=abc,v1={},v1={v1}", "00".repeat(32));
+        assert_eq!(verify(&header, BODY, T), Ok(()));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let headers = ["", "garbage", "t=1700000000", "v1=00", "t=abc,v1=00", "t=1700000000,v1=zz"];
+        for header in headers {
+            assert_eq!(verify(header, BODY, T), Err(SignatureError::Malformed), "{header:?}");
+        }
+    }
+}
@@ -3,7 +3,8 @@ use sqlx::PgPool;
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
-    pub hmac_secret: String
+    pub hmac_secret: String,
+    pub previous_hmac_secrets: Vec<String>,
 }
 
 
@@ -0,0 +1,25 @@
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::db;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+const BATCH_SIZE: i64 = 500;
+// A day is snapshotted once it has been over for this long, so that DB transactions that
+// started before midnight (and carry that timestamp) have committed.
+const SETTLE_SECS: i64 = 5 * 60;
+
+// Fills account_balance_snapshots: each pass writes the next missing day of up to BATCH_SIZE
+// accounts, so new accounts and history missed while the process was down catch up one day per
+// pass until every account has a snapshot for yesterday.
+pub async fn run(pool: PgPool) {
+    loop {
+        match db::insert_next_balance_snapshots(&pool, SETTLE_SECS, BATCH_SIZE).await {
+            // Anything written may have more days behind it.
+            Ok(written) if written > 0 => continue,
+            Ok(_) => {}
+            Err(err) => eprintln!("balance snapshot worker: failed to write snapshots: {err}"),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
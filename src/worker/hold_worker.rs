@@ -0,0 +1,37 @@
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::db;
+use crate::services::hold_service::expire_hold;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 100;
+
+// Releases active holds past their expires_at. Each hold is expired in its own DB transaction
+// (hold row lock, then account row), so a slow or failing one does not hold up the rest.
+pub async fn run(pool: PgPool) {
+    loop {
+        let expired = match db::list_expired_holds(&pool, BATCH_SIZE).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                eprintln!("hold worker: failed to fetch expired holds: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+
+        let mut released = 0;
+        for (business_id, hold_id) in &expired {
+            match expire_hold(&pool, *business_id, *hold_id).await {
+                Ok(true) => released += 1,
+                Ok(false) => {}
+                Err(err) => eprintln!("hold worker: failed to expire hold {hold_id}: {err:?}"),
+            }
+        }
+
+        // A full batch that made progress probably has more behind it.
+        if (expired.len() as i64) < BATCH_SIZE || released == 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
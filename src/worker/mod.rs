@@ -1,3 +1,6 @@
+pub mod balance_snapshot_worker;
+pub mod hold_worker;
+pub mod schedule_worker;
 pub mod webhook_worker;
 
 
@@ -0,0 +1,38 @@
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::db;
+use crate::services::scheduled_transaction_service::run_due_schedule;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 100;
+
+// Executes due scheduled transactions. Each occurrence runs in its own DB transaction
+// (schedule row lock held until the run is recorded); occurrences missed while the scheduler
+// was down are recorded as skipped by `run_due_schedule`, so only the latest one runs.
+pub async fn run(pool: PgPool) {
+    loop {
+        let due = match db::list_due_scheduled_transactions(&pool, BATCH_SIZE).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                eprintln!("scheduler: failed to fetch due schedules: {err}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+
+        let mut ran = 0;
+        for (business_id, schedule_id) in &due {
+            match run_due_schedule(&pool, *business_id, *schedule_id).await {
+                Ok(true) => ran += 1,
+                Ok(false) => {}
+                Err(err) => eprintln!("scheduler: failed to run schedule {schedule_id}: {err:?}"),
+            }
+        }
+
+        // A full batch that made progress probably has more behind it.
+        if (due.len() as i64) < BATCH_SIZE || ran == 0 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
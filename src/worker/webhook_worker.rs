@@ -4,6 +4,7 @@ use sqlx::PgPool;
 use std::time::Duration;
 
 use crate::db;
+use crate::services::webhook_service::{SIGNATURE_HEADER, sign_webhook_payload};
 
 const POLL_INTERVAL: Duration = Duration::from_secs(2);
 const BATCH_SIZE: i64 = 25;
@@ -17,10 +18,19 @@ fn compute_next_retry(attempts: i32) -> DateTime<Utc> {
     Utc::now() + chrono::Duration::seconds(seconds)
 }
 
-async fn deliver_one(client: &Client, url: &str, payload_json: &str) -> Result<(), reqwest::Error> {
+async fn deliver_one(
+    client: &Client,
+    url: &str,
+    secret: &str,
+    payload_json: &str,
+) -> Result<(), reqwest::Error> {
+    // Signed per attempt so the timestamp stays inside the receiver's replay window on retries.
+    let signature = sign_webhook_payload(secret, Utc::now().timestamp(), payload_json.as_bytes());
+
     client
         .post(url)
         .header("content-type", "application/json")
+        .header(SIGNATURE_HEADER, signature)
         .body(payload_json.to_string())
         .send()
         .await?
@@ -47,7 +57,7 @@ pub async fn run(pool: PgPool) {
         }
 
         for ev in due {
-            let result = deliver_one(&client, &ev.url, &ev.payload_json).await;
+            let result = deliver_one(&client, &ev.url, &ev.secret, &ev.payload_json).await;
             match result {
                 Ok(()) => {
                     if let Err(err) = db::mark_webhook_event_delivered(&pool, ev.event_id).await {